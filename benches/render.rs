@@ -0,0 +1,119 @@
+//! Benchmarks writing a definition file for a schema with thousands of types.
+//!
+//! Run with `cargo bench --bench render`, and again with
+//! `cargo bench --bench render --features rayon` to compare rendering the
+//! definitions in parallel. Every definition references the same few types
+//! many times, which also measures the cache of rendered type references.
+
+use std::{
+    collections::HashMap,
+    io,
+    time::{Duration, Instant},
+};
+use typescript_type_def::{
+    type_expr::{
+        DefinedTypeInfo, Ident, ObjectField, TypeDefinition, TypeExpr,
+        TypeInfo, TypeObject, TypeString,
+    },
+    write_definition_file_from_type_infos, DefinitionFileOptions, TypeDef,
+};
+
+/// The number of type definitions in the schema.
+const TYPES: usize = 5000;
+/// The number of definitions in the schema which don't reference others.
+const LEAF_TYPES: usize = 64;
+/// The number of times the definition file is written.
+const ITERATIONS: usize = 20;
+
+fn main() {
+    let type_infos = schema(TYPES);
+    let mut times = (0..ITERATIONS)
+        .map(|_| {
+            let start = Instant::now();
+            let stats = write_definition_file_from_type_infos(
+                io::sink(),
+                DefinitionFileOptions::default(),
+                &type_infos,
+            )
+            .unwrap();
+            let time = start.elapsed();
+            assert!(stats.type_definitions >= TYPES);
+            time
+        })
+        .collect::<Vec<_>>();
+    times.sort();
+    println!(
+        "render {} types: min {:?}, median {:?}, max {:?} ({})",
+        TYPES,
+        times[0],
+        times[ITERATIONS / 2],
+        times[ITERATIONS - 1],
+        if cfg!(feature = "rayon") {
+            "parallel"
+        } else {
+            "single-threaded"
+        },
+    );
+    let total = times.iter().sum::<Duration>();
+    println!("total {:?} for {} iterations", total, ITERATIONS);
+}
+
+/// Returns the type infos of a schema with `count` object definitions spread
+/// over a few namespaces, each of which references one of the leaf
+/// definitions.
+fn schema(count: usize) -> Vec<&'static TypeInfo> {
+    const NAMESPACES: &[Ident] =
+        &[Ident("a"), Ident("b"), Ident("c"), Ident("d")];
+
+    let mut type_infos = Vec::<&'static TypeInfo>::with_capacity(count);
+    for i in 0..count {
+        let mut fields = vec![
+            field("id", TypeExpr::Ref(&<u64 as TypeDef>::INFO)),
+            field("name", TypeExpr::Ref(&<Option<String> as TypeDef>::INFO)),
+            field("tags", TypeExpr::Ref(&<Vec<String> as TypeDef>::INFO)),
+            field(
+                "scores",
+                TypeExpr::Ref(&<HashMap<String, f64> as TypeDef>::INFO),
+            ),
+        ];
+        if i >= LEAF_TYPES {
+            let leaf = type_infos[i % LEAF_TYPES];
+            fields.push(field("parent", TypeExpr::Ref(leaf)));
+        }
+        let path = &NAMESPACES[i % NAMESPACES.len()..][..1];
+        let name = Box::leak(format!("Type{}", i).into_boxed_str());
+        type_infos.push(Box::leak(Box::new(TypeInfo::Defined(
+            DefinedTypeInfo {
+                def: TypeDefinition {
+                    docs: None,
+                    path,
+                    name: Ident(name),
+                    generic_vars: &[],
+                    generic_defaults: &[],
+                    def: TypeExpr::Object(TypeObject {
+                        docs: None,
+                        index_signature: None,
+                        fields: fields.leak(),
+                    }),
+                    emit_schema: false,
+                    interface: None,
+                },
+                generic_args: &[],
+            },
+        ))));
+    }
+    type_infos
+}
+
+fn field(name: &'static str, r#type: TypeExpr) -> ObjectField {
+    ObjectField {
+        docs: None,
+        name: TypeString {
+            docs: None,
+            value: name,
+        },
+        optional: false,
+        readonly: false,
+        r#type,
+    }
+}
@@ -14,7 +14,7 @@ use darling::{
 use proc_macro2::Span;
 use proc_macro_error::{abort, proc_macro_error};
 use quote::{format_ident, quote};
-use std::{ops::Deref, str::FromStr};
+use std::ops::Deref;
 use syn::{
     ext::IdentExt,
     parse::Parser,
@@ -45,6 +45,16 @@ pub fn derive_type_def(
 
     remove_skipped(&mut input.data);
 
+    if **input.default {
+        // a container default makes every field optional
+        if let ast::Data::Struct(ast::Fields { fields, .. }) = &mut input.data {
+            for field in fields {
+                field.default =
+                    SpannedValue::new(FieldDefault(true), input.default.span());
+            }
+        }
+    }
+
     let generics: &mut Generics = &mut input.generics;
     if generics.params.iter().any(|param| match param {
         GenericParam::Type(_) | GenericParam::Lifetime(_) => true,
@@ -107,6 +117,18 @@ pub fn derive_type_def(
         input.generics.split_for_impl();
 
     let info_def = make_info_def(&input);
+    let route_def = make_route_def(&input);
+    let route_impl = route_def.map(|route_def| -> ItemImpl {
+        parse_quote! {
+            impl #impl_generics ::typescript_type_def::fetch_client::Route for
+                #ty_name #ty_generics
+            #where_clause
+            {
+                const ROUTE: ::typescript_type_def::fetch_client::RouteInfo =
+                    #route_def;
+            }
+        }
+    });
 
     (quote! {
         impl #impl_generics ::typescript_type_def::TypeDef for
@@ -115,6 +137,8 @@ pub fn derive_type_def(
         {
             const INFO: ::typescript_type_def::type_expr::TypeInfo = #info_def;
         }
+
+        #route_impl
     })
     .into()
 }
@@ -130,6 +154,22 @@ struct TypeDefInput {
     // type_def
     #[darling(default)]
     namespace: Namespace,
+    #[darling(default)]
+    doc: Option<SpannedValue<String>>,
+    #[darling(default)]
+    example: Option<SpannedValue<String>>,
+    #[darling(default)]
+    brand: SpannedValue<Flag>,
+    #[darling(default)]
+    emit_schema: SpannedValue<Flag>,
+    #[darling(default)]
+    interface: SpannedValue<Flag>,
+    #[darling(default)]
+    type_alias: SpannedValue<Flag>,
+    #[darling(default)]
+    route: Option<SpannedValue<String>>,
+    #[darling(default)]
+    response: Option<SpannedValue<TypeFromMeta>>,
 
     // serde
     #[darling(default)]
@@ -141,10 +181,11 @@ struct TypeDefInput {
     #[darling(default)]
     rename_all: Option<SpannedValue<String>>,
     #[darling(default)]
+    rename_all_fields: Option<SpannedValue<String>>,
+    #[darling(default)]
     rename: Option<SpannedValue<String>>,
     #[darling(default)]
-    #[allow(dead_code)] // doesn't affect JSON
-    transparent: Ignored,
+    transparent: SpannedValue<Flag>,
     #[darling(default)]
     #[allow(dead_code)]
     deny_unknown_fields: Ignored,
@@ -152,20 +193,16 @@ struct TypeDefInput {
     #[allow(dead_code)]
     bound: Ignored,
     #[darling(default)]
-    #[allow(dead_code)]
-    default: Ignored,
+    default: SpannedValue<FieldDefault>,
     #[darling(default)]
     #[allow(dead_code)]
     remote: Ignored,
     #[darling(default)]
-    #[allow(dead_code)]
-    from: Ignored,
+    from: Option<SpannedValue<TypeFromMeta>>,
     #[darling(default)]
-    #[allow(dead_code)]
-    try_from: Ignored,
+    try_from: Option<SpannedValue<TypeFromMeta>>,
     #[darling(default)]
-    #[allow(dead_code)]
-    into: Ignored,
+    into: Option<SpannedValue<TypeFromMeta>>,
     #[darling(default, rename = "crate")]
     #[allow(dead_code)]
     crate_: Ignored,
@@ -181,6 +218,18 @@ struct TypeDefField {
     // type_def
     #[darling(default)]
     type_of: Option<SpannedValue<TypeFromMeta>>,
+    #[darling(default)]
+    literal: Option<SpannedValue<TypeLiteral>>,
+    #[darling(default)]
+    display_from_str: SpannedValue<Flag>,
+    #[darling(default)]
+    optional: SpannedValue<Flag>,
+    #[darling(default)]
+    required: SpannedValue<Flag>,
+    #[darling(default)]
+    doc: Option<SpannedValue<String>>,
+    #[darling(default)]
+    example: Option<SpannedValue<String>>,
 
     // serde
     #[darling(default)]
@@ -193,9 +242,9 @@ struct TypeDefField {
     skip: SpannedValue<Flag>,
     #[darling(default)]
     rename: Option<SpannedValue<String>>,
-    #[darling(default)]
+    #[darling(multiple)]
     #[allow(dead_code)]
-    alias: Ignored,
+    alias: Vec<Ignored>,
     #[darling(default)]
     #[allow(dead_code)]
     skip_serializing: Ignored,
@@ -209,8 +258,7 @@ struct TypeDefField {
     #[allow(dead_code)]
     deserialize_with: Ignored,
     #[darling(default)]
-    #[allow(dead_code)]
-    with: Ignored,
+    with: Option<SpannedValue<String>>,
     #[darling(default)]
     #[allow(dead_code)]
     borrow: Ignored,
@@ -220,15 +268,24 @@ struct TypeDefField {
     #[darling(default)]
     #[allow(dead_code)]
     getter: Ignored,
+
+    // whether this is the only field of a tuple struct or variant whose other
+    // fields are skipped, which serde still serializes as a tuple
+    #[darling(skip)]
+    skipped_siblings: bool,
 }
 
 #[derive(FromVariant)]
-#[darling(attributes(serde), forward_attrs)]
+#[darling(attributes(type_def, serde), forward_attrs)]
 struct TypeDefVariant {
     attrs: Vec<Attribute>,
     ident: Ident,
     fields: ast::Fields<TypeDefField>,
 
+    // type_def
+    #[darling(default)]
+    tag_value: Option<SpannedValue<String>>,
+
     // serde
     #[darling(default)]
     rename_all: Option<SpannedValue<String>>,
@@ -236,9 +293,9 @@ struct TypeDefVariant {
     skip: SpannedValue<Flag>,
     #[darling(default)]
     rename: Option<SpannedValue<String>>,
-    #[darling(default)]
+    #[darling(multiple)]
     #[allow(dead_code)]
-    alias: Ignored,
+    alias: Vec<Ignored>,
     #[darling(default)]
     #[allow(dead_code)]
     skip_serializing: Ignored,
@@ -261,8 +318,7 @@ struct TypeDefVariant {
     #[allow(dead_code)]
     borrow: Ignored,
     #[darling(default)]
-    #[allow(dead_code)]
-    other: Ignored,
+    other: SpannedValue<Flag>,
 }
 
 #[derive(Default)]
@@ -278,6 +334,8 @@ struct FieldDefault(bool);
 
 struct TypeFromMeta(Type);
 
+struct TypeLiteral(Expr);
+
 fn make_info_def(
     TypeDefInput {
         attrs,
@@ -285,14 +343,28 @@ fn make_info_def(
         generics,
         data,
         namespace,
+        doc,
+        example,
+        brand,
+        emit_schema,
+        interface,
+        type_alias,
         tag,
         content,
         untagged,
         rename_all,
+        rename_all_fields,
         rename,
+        transparent,
+        from,
+        try_from,
+        into,
         ..
     }: &TypeDefInput,
 ) -> Expr {
+    // the type is serialized as the type it is converted into, or else it is
+    // deserialized from the type it is converted from
+    let converted = into.as_ref().or(from.as_ref()).or(try_from.as_ref());
     let type_param_decls =
         generics.type_params().flat_map(|TypeParam { ident, .. }| {
             let struct_name = format_ident!("__TypeParam_{}", ident);
@@ -323,6 +395,9 @@ fn make_info_def(
             None => type_ident(&ty_name.unraw().to_string()),
         },
         &match data {
+            _ if converted.is_some() => {
+                type_expr_ref(&***converted.unwrap(), Some(generics))
+            }
             ast::Data::Struct(ast::Fields { fields, style, .. }) => {
                 if let Some(tag) = tag {
                     abort!(tag.span(), "`tag` option is only valid for enums");
@@ -339,30 +414,165 @@ fn make_info_def(
                         "`untagged` option is only valid for enums"
                     );
                 }
+                if let Some(rename_all_fields) = rename_all_fields {
+                    abort!(
+                        rename_all_fields.span(),
+                        "`rename_all_fields` option is only valid for enums"
+                    );
+                }
+                if ***emit_schema
+                    && (***transparent || !matches!(style, ast::Style::Struct))
+                {
+                    abort!(
+                        emit_schema.span(),
+                        "`emit_schema` option is only valid for structs with \
+                         named fields"
+                    );
+                }
+                if ***interface {
+                    if ***transparent || !matches!(style, ast::Style::Struct) {
+                        abort!(
+                            interface.span(),
+                            "`interface` option is only valid for structs \
+                             with named fields"
+                        );
+                    }
+                    if let Some(TypeDefField { flatten, .. }) =
+                        fields.iter().find(|field| **field.flatten)
+                    {
+                        abort!(
+                            flatten.span(),
+                            "flattened fields cannot be used with the \
+                             `interface` option"
+                        );
+                    }
+                }
 
-                match style {
+                let def = match style {
+                    _ if ***transparent => {
+                        if fields.len() != 1 {
+                            abort!(
+                                transparent.span(),
+                                "`transparent` option requires exactly one \
+                                 non-skipped field"
+                            );
+                        }
+                        let TypeDefField { ty, type_of, .. } = &fields[0];
+                        let ty = if let Some(type_of) = type_of {
+                            &***type_of
+                        } else {
+                            ty
+                        };
+                        type_expr_ref(ty, Some(generics))
+                    }
                     ast::Style::Unit => type_expr_ident("null"),
                     ast::Style::Tuple => fields_to_type_expr(
-                        fields, false, rename_all, generics, None,
+                        fields, false, rename_all, generics, None, None,
                     ),
                     ast::Style::Struct => {
                         if fields.is_empty() {
                             type_expr_object([], None)
                         } else {
                             fields_to_type_expr(
-                                fields, true, rename_all, generics, None,
+                                fields, true, rename_all, generics, None, None,
                             )
                         }
                     }
+                };
+                if ***brand {
+                    if !(***transparent
+                        || matches!(style, ast::Style::Tuple)
+                            && fields.len() == 1)
+                    {
+                        abort!(
+                            brand.span(),
+                            "`brand` option is only valid for newtype structs"
+                        );
+                    }
+                    // the brand is the qualified name so that types with the
+                    // same name in different namespaces are distinct
+                    let brand_name = namespace
+                        .parts
+                        .iter()
+                        .map(ToString::to_string)
+                        .chain(std::iter::once(match rename {
+                            Some(rename) => rename.as_str().to_owned(),
+                            None => ty_name.unraw().to_string(),
+                        }))
+                        .collect::<Vec<_>>()
+                        .join(".");
+                    // intersect with a phantom field so that differently
+                    // branded types are not assignable to each other
+                    type_expr_intersection(
+                        [
+                            def,
+                            type_expr_object(
+                                [type_brand_field(&brand_name)],
+                                None,
+                            ),
+                        ],
+                        None,
+                    )
+                } else {
+                    def
                 }
             }
-            ast::Data::Enum(variants) => variants_to_type_expr(
-                variants, tag, content, untagged, rename_all, generics,
-            ),
+            ast::Data::Enum(variants) => {
+                if ***transparent {
+                    abort!(
+                        transparent.span(),
+                        "`transparent` option is only valid for structs"
+                    );
+                }
+                if ***brand {
+                    abort!(
+                        brand.span(),
+                        "`brand` option is only valid for newtype structs"
+                    );
+                }
+                if ***emit_schema {
+                    abort!(
+                        emit_schema.span(),
+                        "`emit_schema` option is only valid for structs with \
+                         named fields"
+                    );
+                }
+                if ***interface {
+                    abort!(
+                        interface.span(),
+                        "`interface` option is only valid for structs with \
+                         named fields"
+                    );
+                }
+                variants_to_type_expr(
+                    variants,
+                    tag,
+                    content,
+                    untagged,
+                    rename_all,
+                    rename_all_fields,
+                    generics,
+                )
+            }
         },
         generics
             .type_params()
             .map(|TypeParam { ident, .. }| type_ident(&ident.to_string())),
+        generics
+            .type_params()
+            .map(|TypeParam { default, .. }| -> Expr {
+                match default {
+                    Some(default) => {
+                        let default = type_expr_ref(default, Some(generics));
+                        parse_quote! {
+                            ::core::option::Option::Some(#default)
+                        }
+                    }
+                    None => parse_quote! {
+                        ::core::option::Option::None
+                    },
+                }
+            }),
         generics.type_params().map(|TypeParam { ident, .. }| {
             type_expr_ref(
                 &Type::Path(TypePath {
@@ -372,7 +582,17 @@ fn make_info_def(
                 None,
             )
         }),
-        extract_type_docs(attrs).as_ref(),
+        extract_type_docs_with(attrs, doc, example).as_ref(),
+        ***emit_schema,
+        match (***interface, ***type_alias) {
+            (true, true) => abort!(
+                type_alias.span(),
+                "cannot give both `interface` and `type_alias` options"
+            ),
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            (false, false) => None,
+        },
     );
     parse_quote! {{
         #(#type_param_decls)*
@@ -380,26 +600,216 @@ fn make_info_def(
     }}
 }
 
+fn make_route_def(
+    TypeDefInput {
+        ident: ty_name,
+        generics,
+        data,
+        rename,
+        rename_all,
+        transparent,
+        from,
+        try_from,
+        into,
+        route,
+        response,
+        ..
+    }: &TypeDefInput,
+) -> Option<Expr> {
+    let route = match route {
+        Some(route) => route,
+        None => {
+            if let Some(response) = response {
+                abort!(
+                    response.span(),
+                    "`response` option requires the `route` option"
+                );
+            }
+            return None;
+        }
+    };
+    if generics.type_params().next().is_some() {
+        abort!(
+            route.span(),
+            "`route` option is not supported for generic types"
+        );
+    }
+    let (method, path) = route
+        .split_once(' ')
+        .filter(|(method, path)| {
+            matches!(*method, "GET" | "POST" | "PUT" | "PATCH" | "DELETE")
+                && path.starts_with('/')
+        })
+        .unwrap_or_else(|| {
+            abort!(
+                route.span(),
+                "`route` must be an HTTP method followed by a path, such as \
+                 \"GET /users/:id\""
+            )
+        });
+    // the fields of the request are only known for plain structs
+    let converted = into.is_some() || from.is_some() || try_from.is_some();
+    let fields = match data {
+        ast::Data::Struct(ast::Fields { style, fields, .. })
+            if !***transparent
+                && !converted
+                && !matches!(style, ast::Style::Tuple) =>
+        {
+            Some(fields.as_slice())
+        }
+        _ => None,
+    };
+    let field_name = |TypeDefField { ident, rename, .. }: &TypeDefField| {
+        ident.as_ref().map(|ident| {
+            serde_rename_ident(ident, rename, rename_all, true).value()
+        })
+    };
+    // path parameters may name a field by its Rust name or its serialized
+    // name, and are replaced by the serialized name
+    let mut params = Vec::new();
+    let path = path
+        .split('/')
+        .map(|segment| {
+            let param = match segment.strip_prefix(':') {
+                Some(param) => param,
+                None => return segment.to_owned(),
+            };
+            let name = fields
+                .into_iter()
+                .flatten()
+                .filter(|field| !***field.flatten)
+                .find(|field| {
+                    field
+                        .ident
+                        .as_ref()
+                        .map_or(false, |ident| ident.unraw() == param)
+                        || field_name(field).as_deref() == Some(param)
+                })
+                .and_then(field_name)
+                .unwrap_or_else(|| {
+                    abort!(
+                        route.span(),
+                        "route parameter `{}` is not a field of the request",
+                        param
+                    )
+                });
+            if name.contains('/') {
+                abort!(
+                    route.span(),
+                    "route parameter `{}` is serialized as {:?}, which \
+                     contains a `/`",
+                    param,
+                    name
+                );
+            }
+            let segment = format!(":{}", name);
+            params.push(name);
+            segment
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    // requests without a body can only be sent through the path
+    if matches!(method, "GET" | "DELETE") {
+        let fields = fields.unwrap_or_else(|| {
+            abort!(
+                route.span(),
+                "requests of `{}` routes must be structs with named fields",
+                method
+            )
+        });
+        for field in fields {
+            if ***field.flatten {
+                abort!(
+                    field.flatten.span(),
+                    "requests of `{}` routes cannot have flattened fields",
+                    method
+                );
+            }
+            if let Some(name) = field_name(field) {
+                if !params.contains(&name) {
+                    abort!(
+                        field.ident.span(),
+                        "field {:?} of a `{}` request must be a route \
+                         parameter, since the request has no body",
+                        name,
+                        method
+                    );
+                }
+            }
+        }
+    }
+    let type_name = match rename {
+        Some(rename) => rename.as_str().to_owned(),
+        None => ty_name.unraw().to_string(),
+    };
+    let name = apply_rename_rule("camelCase", &type_name, false)
+        .expect("camelCase is a known case conversion");
+    let response = match response {
+        Some(response) => {
+            let TypeFromMeta(ty) = &**response;
+            quote! {
+                ::core::option::Option::Some(
+                    &<#ty as ::typescript_type_def::TypeDef>::INFO,
+                )
+            }
+        }
+        None => quote! { ::core::option::Option::None },
+    };
+    Some(parse_quote! {
+        ::typescript_type_def::fetch_client::RouteInfo {
+            name: #name,
+            method: #method,
+            path: #path,
+            request: &<Self as ::typescript_type_def::TypeDef>::INFO,
+            response: #response,
+        }
+    })
+}
+
 fn fields_to_type_expr(
     fields: &[TypeDefField],
     named: bool,
     rename_all: &Option<SpannedValue<String>>,
     generics: &Generics,
     docs: Option<&Expr>,
+    leading_field: Option<Expr>,
 ) -> Expr {
     if fields.is_empty() {
         return if named {
-            type_expr_object(std::iter::empty(), docs)
+            type_expr_object(leading_field, docs)
         } else {
             type_expr_tuple(std::iter::empty(), docs)
         };
     }
+    let newtype = !named
+        && leading_field.is_none()
+        && matches!(
+            fields,
+            [TypeDefField {
+                skipped_siblings: false,
+                ..
+            }]
+        );
     let all_flatten = fields.iter().all(|TypeDefField { flatten, .. }| {
         if ***flatten && !named {
             abort!(flatten.span(), "tuple fields cannot be flattened");
         }
         ***flatten
     });
+    check_duplicate_names(
+        fields.iter().filter_map(
+            |TypeDefField {
+                 ident,
+                 rename,
+                 flatten,
+                 ..
+             }| {
+                let ident = ident.as_ref().filter(|_| !***flatten)?;
+                Some(serde_rename_ident(ident, rename, rename_all, true))
+            },
+        ),
+        "field",
+    );
     let flatten_exprs = fields.iter().filter_map(
         |TypeDefField {
              ty,
@@ -413,12 +823,21 @@ fn fields_to_type_expr(
                 } else {
                     ty
                 };
-                type_expr_ref(ty, Some(generics))
+                // the fields of a flattened `Option` are either all present or
+                // all absent
+                if let Some(inner_ty) = is_option(ty) {
+                    type_expr_partial(type_expr_ref(inner_ty, Some(generics)))
+                } else {
+                    type_expr_ref(ty, Some(generics))
+                }
             })
         },
     );
-    // always put flatten exprs first
-    let exprs = flatten_exprs.chain((!all_flatten).then(|| {
+    // put flatten exprs first unless there is a leading field, which must stay
+    // at the front of the intersection so the tag can be found
+    let tag_first = leading_field.is_some();
+    let make_object = !all_flatten || tag_first;
+    let object = make_object.then(|| {
         // if there are some non-flattened fields, make an expr out of them
         let fields = fields.iter().filter_map(
             |TypeDefField {
@@ -430,8 +849,61 @@ fn fields_to_type_expr(
                  skip_serializing_if,
                  default,
                  rename,
+                 literal,
+                 display_from_str,
+                 with,
+                 optional: force_optional,
+                 required,
+                 doc,
+                 example,
                  ..
              }| {
+                if ***display_from_str {
+                    if ***flatten {
+                        abort!(
+                            display_from_str.span(),
+                            "`display_from_str` option cannot be used on \
+                             flattened fields"
+                        );
+                    }
+                    if type_of.is_some() || literal.is_some() {
+                        abort!(
+                            display_from_str.span(),
+                            "cannot give `display_from_str` option with \
+                             `type_of` or `literal` options"
+                        );
+                    }
+                }
+                if let Some(literal) = literal {
+                    if ***flatten {
+                        abort!(
+                            literal.span(),
+                            "`literal` option cannot be used on flattened \
+                             fields"
+                        );
+                    }
+                    if type_of.is_some() {
+                        abort!(
+                            literal.span(),
+                            "cannot give both `type_of` and `literal` options"
+                        );
+                    }
+                }
+                for flag in [force_optional, required] {
+                    if ***flag && (!named || ***flatten) {
+                        abort!(
+                            flag.span(),
+                            "`optional` and `required` options are only valid \
+                             for named fields which are not flattened"
+                        );
+                    }
+                }
+                if ***force_optional && ***required {
+                    abort!(
+                        required.span(),
+                        "cannot give both `optional` and `required` options"
+                    );
+                }
                 if ***flatten {
                     if !named {
                         abort!(
@@ -446,6 +918,34 @@ fn fields_to_type_expr(
                 } else {
                     ty
                 };
+                let literal =
+                    literal.as_ref().map(|literal| (***literal).clone());
+                // chrono timestamps are numbers instead of strings
+                let timestamp = type_of.is_none()
+                    && with.as_ref().map_or(false, |with| {
+                        parse_str::<Path>(with)
+                            .map_or(false, |path| is_chrono_timestamp(&path))
+                    });
+                // fields serialized with `Display` are always strings
+                let type_expr = |ty: &Type| {
+                    if ***display_from_str {
+                        type_expr_ident("string")
+                    } else if timestamp {
+                        if is_option(ty).is_some() {
+                            type_expr_union(
+                                [
+                                    type_expr_ident("number"),
+                                    type_expr_ident("null"),
+                                ],
+                                None,
+                            )
+                        } else {
+                            type_expr_ident("number")
+                        }
+                    } else {
+                        type_expr_ref(ty, Some(generics))
+                    }
+                };
                 if let Some(field_name) = field_name {
                     let name = type_string(
                         &serde_rename_ident(
@@ -455,38 +955,54 @@ fn fields_to_type_expr(
                         None,
                     );
                     let mut ty = ty;
-                    let optional = if let Some(skip_serializing_if) =
+                    let optional = if ***required {
+                        false
+                    } else if let Some(skip_serializing_if) =
                         skip_serializing_if
                     {
+                        // a `None` value is absent rather than `null`
                         if let Some(inner_ty) = is_option(ty) {
-                            if parse_str::<Path>(skip_serializing_if).unwrap()
-                                == parse_str::<Path>("Option::is_none").unwrap()
-                            {
+                            if is_option_is_none(
+                                &parse_str::<Path>(skip_serializing_if)
+                                    .unwrap(),
+                            ) {
                                 ty = inner_ty;
                             }
                         }
                         true
                     } else {
-                        ***default
+                        ***force_optional || ***default
                     };
-                    let r#type = type_expr_ref(ty, Some(generics));
+                    let r#type = literal.unwrap_or_else(|| type_expr(ty));
                     Some(type_object_field(
                         &name,
                         optional,
                         &r#type,
-                        extract_type_docs(attrs).as_ref(),
+                        extract_type_docs_with(attrs, doc, example).as_ref(),
                     ))
                 } else {
-                    Some(type_expr_ref(ty, Some(generics)))
+                    Some(literal.unwrap_or_else(|| type_expr(ty)))
                 }
             },
         );
+        let fields = leading_field.into_iter().chain(fields);
         if named {
             type_expr_object(fields, docs)
         } else {
-            type_expr_tuple(fields, docs)
+            let mut fields = fields.collect::<Vec<_>>();
+            // a newtype is serialized as its field
+            if newtype {
+                fields.remove(0)
+            } else {
+                type_expr_tuple(fields, docs)
+            }
         }
-    }));
+    });
+    let exprs = if tag_first {
+        object.into_iter().chain(flatten_exprs).collect::<Vec<_>>()
+    } else {
+        flatten_exprs.chain(object).collect()
+    };
     type_expr_intersection(exprs, None)
 }
 
@@ -496,8 +1012,41 @@ fn variants_to_type_expr(
     content: &Option<SpannedValue<String>>,
     untagged: &SpannedValue<Flag>,
     variant_rename_all: &Option<SpannedValue<String>>,
+    rename_all_fields: &Option<SpannedValue<String>>,
     generics: &Generics,
 ) -> Expr {
+    if let (Some(tag), Some(content)) = (tag, content) {
+        if tag.as_str() == content.as_str() {
+            abort!(
+                content.span(),
+                "`tag` and `content` options must have different values"
+            );
+        }
+    }
+    if !***untagged {
+        check_duplicate_names(
+            variants.iter().map(
+                |TypeDefVariant {
+                     ident,
+                     rename,
+                     tag_value,
+                     ..
+                 }| {
+                    if let Some(tag_value) = tag_value {
+                        LitStr::new(tag_value.as_str(), tag_value.span())
+                    } else {
+                        serde_rename_ident(
+                            ident,
+                            rename,
+                            variant_rename_all,
+                            false,
+                        )
+                    }
+                },
+            ),
+            "variant",
+        );
+    }
     type_expr_union(
         variants.iter().map(
             |TypeDefVariant {
@@ -506,16 +1055,56 @@ fn variants_to_type_expr(
                  fields: ast::Fields { style, fields, .. },
                  rename_all: field_rename_all,
                  rename: variant_rename,
+                 other,
+                 tag_value,
                  ..
              }| {
-                let variant_name = serde_rename_ident(
-                    variant_name,
-                    variant_rename,
-                    variant_rename_all,
-                    false,
-                );
+                let variant_name = if let Some(tag_value) = tag_value {
+                    if ***untagged {
+                        abort!(
+                            tag_value.span(),
+                            "`tag_value` option is not valid for untagged \
+                             enums"
+                        );
+                    }
+                    if ***other {
+                        abort!(
+                            tag_value.span(),
+                            "`tag_value` option is not valid for `other` \
+                             variants"
+                        );
+                    }
+                    LitStr::new(tag_value.as_str(), tag_value.span())
+                } else {
+                    serde_rename_ident(
+                        variant_name,
+                        variant_rename,
+                        variant_rename_all,
+                        false,
+                    )
+                };
+                // a variant's own `rename_all` takes precedence over the
+                // container's `rename_all_fields`
+                let field_rename_all = if field_rename_all.is_some() {
+                    field_rename_all
+                } else {
+                    rename_all_fields
+                };
+                if ***other && !matches!(style, ast::Style::Unit) {
+                    abort!(
+                        other.span(),
+                        "`other` variant must be a unit variant"
+                    );
+                }
+                // a catch-all variant is deserialized from any tag value
+                let unit_tag = if ***other {
+                    type_expr_ident("string")
+                } else {
+                    type_expr_string(&variant_name.value(), None)
+                };
                 match (tag, content, ***untagged) {
                     (None, None, false) => match style {
+                        ast::Style::Unit if ***other => unit_tag,
                         ast::Style::Unit => type_expr_string(
                             &variant_name.value(),
                             extract_type_docs(attrs).as_ref(),
@@ -531,6 +1120,7 @@ fn variants_to_type_expr(
                                         field_rename_all,
                                         generics,
                                         None,
+                                        None,
                                     ),
                                     extract_type_docs(attrs).as_ref(),
                                 )],
@@ -547,6 +1137,7 @@ fn variants_to_type_expr(
                                 field_rename_all,
                                 generics,
                                 extract_type_docs(attrs).as_ref(),
+                                None,
                             )
                         }
                     },
@@ -555,15 +1146,13 @@ fn variants_to_type_expr(
                             [type_object_field(
                                 &type_string(tag, None),
                                 false,
-                                &type_expr_string(&variant_name.value(), None),
+                                &unit_tag,
                                 extract_type_docs(attrs).as_ref(),
                             )],
                             None,
                         ),
-                        ast::Style::Tuple | ast::Style::Struct => {
-                            if matches!(style, ast::Style::Tuple)
-                                && fields.len() != 1
-                            {
+                        ast::Style::Tuple => {
+                            if fields.len() != 1 {
                                 abort!(
                                     tag.span(),
                                     "cannot tag enums with tuple variants"
@@ -585,22 +1174,44 @@ fn variants_to_type_expr(
                                     ),
                                     fields_to_type_expr(
                                         fields,
-                                        matches!(style, ast::Style::Struct),
+                                        false,
                                         field_rename_all,
                                         generics,
                                         None,
+                                        None,
                                     ),
                                 ],
                                 None,
                             )
                         }
+                        // put the tag field first in the same object as the
+                        // variant's own fields
+                        ast::Style::Struct => {
+                            check_tag_conflict(fields, tag, field_rename_all);
+                            fields_to_type_expr(
+                                fields,
+                                true,
+                                field_rename_all,
+                                generics,
+                                None,
+                                Some(type_object_field(
+                                    &type_string(tag, None),
+                                    false,
+                                    &type_expr_string(
+                                        &variant_name.value(),
+                                        None,
+                                    ),
+                                    extract_type_docs(attrs).as_ref(),
+                                )),
+                            )
+                        }
                     },
                     (Some(tag), Some(content), false) => match style {
                         ast::Style::Unit => type_expr_object(
                             [type_object_field(
                                 &type_string(tag, None),
                                 false,
-                                &type_expr_string(&variant_name.value(), None),
+                                &unit_tag,
                                 extract_type_docs(attrs).as_ref(),
                             )],
                             None,
@@ -626,6 +1237,7 @@ fn variants_to_type_expr(
                                             field_rename_all,
                                             generics,
                                             None,
+                                            None,
                                         ),
                                         None,
                                     ),
@@ -653,6 +1265,54 @@ fn variants_to_type_expr(
     )
 }
 
+/// Aborts if any of the named fields of an internally tagged variant would be
+/// serialized with the same name as the tag field.
+fn check_tag_conflict(
+    fields: &[TypeDefField],
+    tag: &SpannedValue<String>,
+    rename_all: &Option<SpannedValue<String>>,
+) {
+    for TypeDefField {
+        ident,
+        rename,
+        flatten,
+        ..
+    } in fields
+    {
+        if let Some(ident) = ident {
+            if !***flatten
+                && serde_rename_ident(ident, rename, rename_all, true).value()
+                    == tag.as_str()
+            {
+                abort!(
+                    ident.span(),
+                    "field name conflicts with the `tag` option value {:?}",
+                    tag.as_str()
+                );
+            }
+        }
+    }
+}
+
+/// Aborts if any of the given serialized names of fields or variants are the
+/// same, since the emitted type would have duplicate keys or discriminants.
+fn check_duplicate_names(names: impl IntoIterator<Item = LitStr>, kind: &str) {
+    let mut seen = Vec::<String>::new();
+    for name in names {
+        let value = name.value();
+        if seen.contains(&value) {
+            abort!(
+                name.span(),
+                "{} name {:?} conflicts with another {}",
+                kind,
+                value,
+                kind
+            );
+        }
+        seen.push(value);
+    }
+}
+
 fn type_ident(ident: &str) -> Expr {
     parse_quote! {
         ::typescript_type_def::type_expr::Ident(
@@ -681,6 +1341,18 @@ fn type_expr_ident(ident: &str) -> Expr {
     }
 }
 
+fn type_expr_partial(expr: Expr) -> Expr {
+    parse_quote! {
+        ::typescript_type_def::type_expr::TypeExpr::Name(
+            ::typescript_type_def::type_expr::TypeName {
+                path: &[],
+                name: ::typescript_type_def::type_expr::Ident("Partial"),
+                generic_args: &[#expr],
+            },
+        )
+    }
+}
+
 fn type_expr_ref(ty: &Type, generics: Option<&Generics>) -> Expr {
     let mut ty = ty.clone();
 
@@ -731,18 +1403,14 @@ fn type_expr_tuple(
     docs: Option<&Expr>,
 ) -> Expr {
     let docs = wrap_optional_docs(docs);
-    let exprs = exprs.into_iter().collect::<Vec<_>>();
-    if exprs.len() == 1 {
-        exprs.into_iter().next().unwrap()
-    } else {
-        parse_quote! {
-            ::typescript_type_def::type_expr::TypeExpr::Tuple(
-                ::typescript_type_def::type_expr::TypeTuple {
-                    docs: #docs,
-                    elements: &[#(#exprs,)*],
-                },
-            )
-        }
+    let exprs = exprs.into_iter();
+    parse_quote! {
+        ::typescript_type_def::type_expr::TypeExpr::Tuple(
+            ::typescript_type_def::type_expr::TypeTuple {
+                docs: #docs,
+                elements: &[#(#exprs,)*],
+            },
+        )
     }
 }
 
@@ -758,6 +1426,21 @@ fn type_object_field(
             docs: #docs,
             name: #name,
             optional: #optional,
+            readonly: false,
+            r#type: #r#type,
+        }
+    }
+}
+
+fn type_brand_field(brand_name: &str) -> Expr {
+    let name = type_string("__brand", None);
+    let r#type = type_expr_string(brand_name, None);
+    parse_quote! {
+        ::typescript_type_def::type_expr::ObjectField {
+            docs: ::core::option::Option::None,
+            name: #name,
+            optional: false,
+            readonly: true,
             r#type: #r#type,
         }
     }
@@ -825,12 +1508,24 @@ fn type_info(
     name: &Expr,
     def: &Expr,
     generic_vars: impl IntoIterator<Item = Expr>,
+    generic_defaults: impl IntoIterator<Item = Expr>,
     generic_args: impl IntoIterator<Item = Expr>,
     docs: Option<&Expr>,
+    emit_schema: bool,
+    interface: Option<bool>,
 ) -> Expr {
     let docs = wrap_optional_docs(docs);
+    let interface: Expr = match interface {
+        Some(interface) => parse_quote! {
+            ::core::option::Option::Some(#interface)
+        },
+        None => parse_quote! {
+            ::core::option::Option::None
+        },
+    };
     let path_parts = path_parts.into_iter();
     let generic_vars = generic_vars.into_iter();
+    let generic_defaults = generic_defaults.into_iter();
     let generic_args = generic_args.into_iter();
     parse_quote! {
         ::typescript_type_def::type_expr::TypeInfo::Defined(
@@ -840,7 +1535,10 @@ fn type_info(
                     path: &[#(#path_parts,)*],
                     name: #name,
                     generic_vars: &[#(#generic_vars,)*],
+                    generic_defaults: &[#(#generic_defaults,)*],
                     def: #def,
+                    emit_schema: #emit_schema,
+                    interface: #interface,
                 },
                 generic_args: &[#(#generic_args,)*],
             },
@@ -849,6 +1547,17 @@ fn type_info(
 }
 
 fn extract_type_docs(attrs: &[Attribute]) -> Option<Expr> {
+    extract_type_docs_with(attrs, &None, &None)
+}
+
+/// Extracts the doc comments from `attrs`, followed by the TypeScript-only
+/// docs given by `#[type_def(doc = "...")]` and the `@example` block given by
+/// `#[type_def(example = "...")]` if any.
+fn extract_type_docs_with(
+    attrs: &[Attribute],
+    doc: &Option<SpannedValue<String>>,
+    example: &Option<SpannedValue<String>>,
+) -> Option<Expr> {
     let mut lines = attrs
         .iter()
         .filter_map(|attr| {
@@ -876,13 +1585,34 @@ fn extract_type_docs(attrs: &[Attribute]) -> Option<Expr> {
                 )
             }
         })
-        .min()?;
-    if min_indent > 0 {
-        for line in &mut lines {
-            if !line.is_empty() {
-                *line = line.split_off(min_indent);
+        .min();
+    match min_indent {
+        Some(min_indent) if min_indent > 0 => {
+            for line in &mut lines {
+                if !line.is_empty() {
+                    *line = line.split_off(min_indent);
+                }
             }
         }
+        Some(_) => {}
+        None => lines.clear(),
+    }
+    if let Some(doc) = doc {
+        if !lines.is_empty() {
+            // separate from the Rust docs with an empty line
+            lines.push(String::new());
+        }
+        lines.extend(doc.lines().map(ToOwned::to_owned));
+    }
+    if let Some(example) = example {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push("@example".to_owned());
+        lines.extend(example.lines().map(ToOwned::to_owned));
+    }
+    if lines.is_empty() {
+        return None;
     }
     let docs = lines.join("\n");
     Some(parse_quote! {
@@ -917,19 +1647,9 @@ fn serde_rename_ident(
     } else {
         let ident = ident.unraw().to_string();
         let ident = if let Some(rename_all) = rename_all {
-            match rename_all.as_str() {
-                "lowercase" => ident.to_lowercase(),
-                "UPPERCASE" => ident.to_uppercase(),
-                _ => match ident_case::RenameRule::from_str(rename_all) {
-                    Ok(rename_all) => match is_field {
-                        true => rename_all.apply_to_field(ident),
-                        false => rename_all.apply_to_variant(ident),
-                    },
-                    Err(()) => {
-                        abort!(rename_all.span(), "unknown case conversion")
-                    }
-                },
-            }
+            apply_rename_rule(rename_all, &ident, is_field).unwrap_or_else(
+                || abort!(rename_all.span(), "unknown case conversion"),
+            )
         } else {
             ident
         };
@@ -937,11 +1657,87 @@ fn serde_rename_ident(
     }
 }
 
+/// Applies a `rename_all` case conversion to a field or variant name,
+/// returning `None` if the conversion is unknown.
+///
+/// Like serde, this assumes field names are `snake_case` and variant names
+/// are `PascalCase`.
+fn apply_rename_rule(
+    rule: &str,
+    ident: &str,
+    is_field: bool,
+) -> Option<String> {
+    fn lower_first(s: &str) -> String {
+        let mut chars = s.chars();
+        chars
+            .next()
+            .map(|first| {
+                first.to_ascii_lowercase().to_string() + chars.as_str()
+            })
+            .unwrap_or_default()
+    }
+
+    Some(if is_field {
+        match rule {
+            "lowercase" | "snake_case" => ident.to_owned(),
+            "UPPERCASE" | "SCREAMING_SNAKE_CASE" => ident.to_ascii_uppercase(),
+            "PascalCase" => pascal_case_field(ident),
+            "camelCase" => lower_first(&pascal_case_field(ident)),
+            "kebab-case" => ident.replace('_', "-"),
+            "SCREAMING-KEBAB-CASE" => {
+                ident.to_ascii_uppercase().replace('_', "-")
+            }
+            _ => return None,
+        }
+    } else {
+        match rule {
+            "lowercase" => ident.to_ascii_lowercase(),
+            "UPPERCASE" => ident.to_ascii_uppercase(),
+            "PascalCase" => ident.to_owned(),
+            "camelCase" => lower_first(ident),
+            "snake_case" => snake_case_variant(ident),
+            "SCREAMING_SNAKE_CASE" => {
+                snake_case_variant(ident).to_ascii_uppercase()
+            }
+            "kebab-case" => snake_case_variant(ident).replace('_', "-"),
+            "SCREAMING-KEBAB-CASE" => snake_case_variant(ident)
+                .to_ascii_uppercase()
+                .replace('_', "-"),
+            _ => return None,
+        }
+    })
+}
+
+fn pascal_case_field(ident: &str) -> String {
+    let mut pascal = String::new();
+    let mut capitalize = true;
+    for ch in ident.chars() {
+        if ch == '_' {
+            capitalize = true;
+        } else if capitalize {
+            pascal.push(ch.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            pascal.push(ch);
+        }
+    }
+    pascal
+}
+
+fn snake_case_variant(ident: &str) -> String {
+    let mut snake = String::new();
+    for (i, ch) in ident.char_indices() {
+        if i > 0 && ch.is_uppercase() {
+            snake.push('_');
+        }
+        snake.push(ch.to_ascii_lowercase());
+    }
+    snake
+}
+
 fn remove_skipped(data: &mut ast::Data<TypeDefVariant, TypeDefField>) {
     match data {
-        ast::Data::Struct(ast::Fields { fields, .. }) => {
-            remove_if(fields, |TypeDefField { skip, .. }| ***skip);
-        }
+        ast::Data::Struct(fields) => remove_skipped_fields(fields),
         ast::Data::Enum(variants) => {
             remove_if(
                 variants,
@@ -953,7 +1749,7 @@ fn remove_skipped(data: &mut ast::Data<TypeDefVariant, TypeDefField>) {
                     if ***skip {
                         return true;
                     }
-                    remove_if(fields, |TypeDefField { skip, .. }| ***skip);
+                    remove_skipped_fields(fields);
                     false
                 },
             );
@@ -961,6 +1757,19 @@ fn remove_skipped(data: &mut ast::Data<TypeDefVariant, TypeDefField>) {
     }
 }
 
+fn remove_skipped_fields(
+    ast::Fields { style, fields, .. }: &mut ast::Fields<TypeDefField>,
+) {
+    let len = fields.len();
+    remove_if(fields, |TypeDefField { skip, .. }| ***skip);
+    // only a tuple which has a single field in the source is a newtype
+    if matches!(style, ast::Style::Tuple) && len > 1 {
+        if let [field] = fields.as_mut_slice() {
+            field.skipped_siblings = true;
+        }
+    }
+}
+
 impl Deref for Flag {
     type Target = bool;
 
@@ -1008,6 +1817,40 @@ impl FromMeta for FieldDefault {
     }
 }
 
+impl Deref for TypeLiteral {
+    type Target = Expr;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromMeta for TypeLiteral {
+    fn from_value(value: &Lit) -> Result<Self, darling::Error> {
+        match value {
+            Lit::Str(lit_str) => {
+                Ok(Self(type_expr_string(&lit_str.value(), None)))
+            }
+            Lit::Int(lit_int) => {
+                Ok(Self(type_expr_ident(lit_int.base10_digits())))
+            }
+            Lit::Float(lit_float) => {
+                Ok(Self(type_expr_ident(lit_float.base10_digits())))
+            }
+            Lit::Bool(lit_bool) => {
+                Ok(Self(type_expr_ident(if lit_bool.value {
+                    "true"
+                } else {
+                    "false"
+                })))
+            }
+            _ => Err(darling::Error::custom(
+                "expected string, number, or boolean literal",
+            )),
+        }
+    }
+}
+
 impl Deref for TypeFromMeta {
     type Target = Type;
 
@@ -1022,6 +1865,55 @@ impl FromMeta for TypeFromMeta {
     }
 }
 
+/// Returns whether `path` is `Option::is_none`, optionally qualified with
+/// `std::option` or `core::option`.
+fn is_option_is_none(path: &Path) -> bool {
+    let segments = path
+        .segments
+        .iter()
+        .map(|PathSegment { ident, arguments }| {
+            arguments.is_empty().then(|| ident.to_string())
+        })
+        .collect::<Option<Vec<_>>>();
+    match segments.as_deref() {
+        Some([option, is_none]) => option == "Option" && is_none == "is_none",
+        Some([krate, module, option, is_none]) => {
+            (krate == "std" || krate == "core")
+                && module == "option"
+                && option == "Option"
+                && is_none == "is_none"
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether `path` is one of the `chrono` modules which serialize dates
+/// as Unix timestamps, such as `ts_seconds`, optionally qualified with `serde`
+/// or `chrono::serde`.
+fn is_chrono_timestamp(path: &Path) -> bool {
+    let segments = path
+        .segments
+        .iter()
+        .map(|PathSegment { ident, arguments }| {
+            arguments.is_empty().then(|| ident.to_string())
+        })
+        .collect::<Option<Vec<_>>>();
+    let module: &str = match segments.as_deref() {
+        Some([module]) => module,
+        Some([serde, module]) if serde == "serde" => module,
+        Some([krate, serde, module])
+            if krate == "chrono" && serde == "serde" =>
+        {
+            module
+        }
+        _ => return false,
+    };
+    matches!(
+        module.strip_suffix("_option").unwrap_or(module),
+        "ts_seconds" | "ts_milliseconds" | "ts_microseconds" | "ts_nanoseconds"
+    )
+}
+
 fn is_option(ty: &Type) -> Option<&Type> {
     if let Type::Path(TypePath {
         qself: None,
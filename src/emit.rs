@@ -3,7 +3,16 @@ use crate::type_expr::{
     TypeArray, TypeDefinition, TypeExpr, TypeInfo, TypeIntersection, TypeName,
     TypeObject, TypeString, TypeTuple, TypeUnion,
 };
-use std::{borrow::Cow, io};
+use alloc::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec::Vec,
+};
+use core::fmt::{self, Write as _};
+#[cfg(feature = "std")]
+use std::io;
 
 /// A Rust type that has a corresponding TypeScript type definition.
 ///
@@ -109,9 +118,9 @@ use std::{borrow::Cow, io};
 /// | [`bool`] | `boolean` |
 /// | [`String`], [`str`] | `string` |
 /// | [`char`] | `string` |
-/// | [`PathBuf`](std::path::PathBuf), [`Path`](std::path::Path) | `string` |
-/// | [`CString`](std::ffi::CString), [`CStr`](std::ffi::CStr), [`OsString`](std::ffi::OsString), [`OsStr`](std::ffi::OsStr) | `string` |
-/// | [`IpAddr`](std::net::IpAddr), [`Ipv4Addr`](std::net::Ipv4Addr), [`Ipv6Addr`](std::net::Ipv6Addr) | `string` |
+/// | [`PathBuf`](std::path::PathBuf), [`Path`](std::path::Path) | `string`[^std] |
+/// | [`CString`](std::ffi::CString), [`CStr`](std::ffi::CStr), [`OsString`](std::ffi::OsString), [`OsStr`](std::ffi::OsStr) | `string`[^std] |
+/// | [`IpAddr`](std::net::IpAddr), [`Ipv4Addr`](std::net::Ipv4Addr), [`Ipv6Addr`](std::net::Ipv6Addr) | `string`[^std] |
 /// | numeric types | `number`[^number] |
 /// | [`()`](unit) | `null` |
 /// | [`(A, B, C)`](tuple) | `[A, B, C]` |
@@ -119,15 +128,23 @@ use std::{borrow::Cow, io};
 // FIXME: https://github.com/rust-lang/rust/issues/86375
 /// | [`Option<T>`] | <code>T \| null</code> |
 /// | [`Vec<T>`], [`[T]`](slice) | `T[]` |
-/// | [`HashSet<T>`](std::collections::HashSet) | `T[]` |
+/// | [`HashSet<T, S>`](std::collections::HashSet) (with any hasher `S`) | `T[]`[^std] |
 /// | [`BTreeSet<T>`](std::collections::BTreeSet) | `T[]` |
-/// | [`HashMap<K, V>`](std::collections::HashMap) | `Record<K, V>` |
+/// | [`HashMap<K, V, S>`](std::collections::HashMap) (with any hasher `S`) | `Record<K, V>`[^std] |
 /// | [`BTreeMap<K, V>`](std::collections::BTreeMap) | `Record<K, V>` |
 /// | [`&'static T`](reference) | `T` |
 /// | [`Box<T>`] | `T` |
-/// | [`Cow<'static, T>`](std::borrow::Cow) | `T` |
+/// | [`Cow<'static, T>`](std::borrow::Cow) (e.g. `Cow<'static, str>` is `string` and `Cow<'static, [T]>` is `T[]`) | `T` |
 /// | [`PhantomData<T>`](std::marker::PhantomData) | `T` |
+/// | [`PhantomPinned`](std::marker::PhantomPinned) | `null`[^phantom_pinned] |
+/// | [`Reverse<T>`](std::cmp::Reverse) | `T` |
 /// | [`Result<T, E>`](std::result::Result) | <code>{ Ok: T } \| { Err: E }</code> |
+/// | [`Bound<T>`](std::ops::Bound) | <code>{ Included: T } \| { Excluded: T } \| "Unbounded"</code> |
+/// | [`Range<T>`](std::ops::Range), [`RangeInclusive<T>`](std::ops::RangeInclusive) | `{ start: T; end: T }` |
+/// | [`RangeFrom<T>`](std::ops::RangeFrom) | `{ start: T }` |
+/// | [`RangeTo<T>`](std::ops::RangeTo) | `{ end: T }` |
+/// | [`Duration`](std::time::Duration) | `{ secs: U64; nanos: U32 }`[^time] |
+/// | [`SystemTime`](std::time::SystemTime) | `{ secs_since_epoch: U64; nanos_since_epoch: U32 }`[^time][^std] |
 ///
 /// ### [`serde_json`] Types
 ///
@@ -140,10 +157,70 @@ use std::{borrow::Cow, io};
 /// | [`Map<K, V>`](serde_json::Map) | `Record<K, V>` |
 /// | [`Number`](serde_json::Number) | `number` |
 ///
+/// ### [`arrayvec`] Types
+///
+/// [`TypeDef`] is implemented for types from the [`arrayvec`] crate (when the
+/// `arrayvec` crate feature is enabled) as follows:
+///
+/// | Rust type | TypeScript type |
+/// |---|---|
+/// | [`ArrayVec<T, CAP>`](arrayvec::ArrayVec) | `T[]` |
+/// | [`ArrayString<CAP>`](arrayvec::ArrayString) | `string` |
+///
+/// ### [`smallvec`] Types
+///
+/// [`TypeDef`] is implemented for types from the [`smallvec`] crate (when the
+/// `smallvec` crate feature is enabled) as follows:
+///
+/// | Rust type | TypeScript type |
+/// |---|---|
+/// | [`SmallVec<[T; N]>`](smallvec::SmallVec) | `T[]` |
+///
+/// ### [`chrono`] Types
+///
+/// [`TypeDef`] is implemented for types from the [`chrono`] crate (when the
+/// `chrono` crate feature is enabled) as follows:
+///
+/// | Rust type | TypeScript type |
+/// |---|---|
+/// | [`DateTime<Tz>`](chrono::DateTime) | `string` |
+/// | [`NaiveDate`](chrono::NaiveDate), [`NaiveTime`](chrono::NaiveTime), [`NaiveDateTime`](chrono::NaiveDateTime) | `string` |
+/// | [`Weekday`](chrono::Weekday), [`Month`](chrono::Month) | `string` |
+///
+/// [`Duration`](chrono::Duration) has no `serde` implementation, so it has no
+/// [`TypeDef`] implementation either. Use `#[type_def(type_of = "...")]` to
+/// match the encoding you use for it.
+///
+/// ### [`jiff`] Types
+///
+/// [`TypeDef`] is implemented for types from the [`jiff`] crate (when the
+/// `jiff` crate feature is enabled) as follows:
+///
+/// | Rust type | TypeScript type |
+/// |---|---|
+/// | [`Timestamp`](jiff::Timestamp) | `string` |
+/// | [`Date`](jiff::civil::Date), [`Time`](jiff::civil::Time), [`DateTime`](jiff::civil::DateTime) | `string` |
+/// | [`Span`](jiff::Span) | `string` |
+///
+/// This matches the `serde` implementations of these types (with the `serde`
+/// feature of `jiff`), which serialize them as ISO 8601 strings.
+///
 /// [^number]: `std` numeric types are emitted as named aliases converted to
 /// PascalCase (e.g. `Usize`, `I32`, `F64`, `NonZeroI8`, etc.). Since they are
 /// simple aliases, they do not enforce anything in TypeScript about the Rust
 /// types' numeric bounds, but serve to document their intended range.
+///
+/// [^time]: This matches the default `serde` representation of these types. If
+/// you serialize them differently (e.g. as a number of milliseconds or as a
+/// string using a `#[serde(with = "...")]` module), use
+/// `#[type_def(type_of = "u64")]` or `#[type_def(type_of = "String")]` on the
+/// field to match the encoding you use.
+///
+/// [^phantom_pinned]: `serde` does not implement its traits for
+/// `PhantomPinned`, so a field of this type needs a custom serializer, and it
+/// is usually better to skip it with `#[serde(skip)]`.
+///
+/// [^std]: Only implemented when the `std` crate feature is enabled.
 pub trait TypeDef: 'static {
     /// A constant value describing the structure of this type.
     ///
@@ -152,10 +229,28 @@ pub trait TypeDef: 'static {
 }
 
 pub(crate) struct EmitCtx<'ctx> {
-    w: &'ctx mut dyn io::Write,
-    root_namespace: Option<&'ctx str>,
+    w: &'ctx mut dyn fmt::Write,
+    options: DefinitionFileOptions<'ctx>,
     indent: usize,
     stats: Stats,
+    inlined: &'ctx BTreeSet<u64>,
+    /// Whether each definition is inlined, keyed by the address of the
+    /// definition, so that it is only hashed once.
+    inline_decisions: BTreeMap<usize, bool>,
+    shared: &'ctx SharedDefinitions,
+    /// Rendered type references, keyed by the address of their type info, the
+    /// indentation they were rendered at, and the address of the definition
+    /// which unions were hoisted out of (or 0).
+    rendered: BTreeMap<(usize, usize, usize), String>,
+    /// The definition which wide unions are currently hoisted out of.
+    hoist_target: Option<&'static TypeDefinition>,
+    /// The unions hoisted out of the current definition so far.
+    hoisted: Vec<TypeUnion>,
+    /// The qualified names of the aliases generated for definitions, such as
+    /// `FooPartial`, which must not be the same as any definition's name.
+    generated: Vec<String>,
+    /// The generated name which was the same as a definition's name, if any.
+    name_conflict: Option<String>,
 }
 
 impl EmitCtx<'_> {
@@ -171,6 +266,19 @@ impl EmitCtx<'_> {
         self.indent -= 1;
     }
 
+    fn is_inlined(&mut self, def: &'static TypeDefinition) -> bool {
+        if self.inlined.is_empty() {
+            return false;
+        }
+        let inlined = self.inlined;
+        *self
+            .inline_decisions
+            .entry(def as *const TypeDefinition as usize)
+            .or_insert_with(|| {
+                inlined.contains(&crate::iter_def_deps::hash_type_def(def))
+            })
+    }
+
     fn current_indentation(&self) -> Cow<'static, str> {
         // hard-code common values to avoid frequent string construction
         match self.indent {
@@ -184,18 +292,45 @@ impl EmitCtx<'_> {
 }
 
 pub(crate) trait Emit {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()>;
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result;
 }
 
 /// Options for customizing the output of [`write_definition_file`].
 ///
 /// The default options are:
 /// ```
-/// # use typescript_type_def::DefinitionFileOptions;
+/// # use typescript_type_def::{
+/// #     DefinitionFileOptions, HeaderStyle, LineEnding, MapStyle,
+/// #     ModuleSystem,
+/// # };
 /// # let default =
 /// DefinitionFileOptions {
 ///     header: Some("// AUTO-GENERATED by typescript-type-def\n"),
 ///     root_namespace: Some("types"),
+///     strict_empty_objects: false,
+///     all_type_names: None,
+///     line_ending: LineEnding::Lf,
+///     module_system: ModuleSystem::Esm,
+///     readonly_arrays: false,
+///     inline_single_use: false,
+///     by_kind_maps: false,
+///     partial_aliases: false,
+///     finite_floats: false,
+///     external_imports: &[],
+///     any_type: None,
+///     type_override: None,
+///     prettier_ignore: false,
+///     header_style: HeaderStyle::Verbatim,
+///     max_union_width: None,
+///     map_style: MapStyle::Record,
+///     utf8_bom: false,
+///     tag_aliases: false,
+///     package_docs: None,
+///     objects_as_interfaces: false,
+///     declare_module: None,
+///     handler_maps: false,
+///     root_namespace_docs: None,
+///     generated_from: None,
 /// }
 /// # ;
 /// # assert_eq!(default, Default::default());
@@ -222,6 +357,326 @@ pub struct DefinitionFileOptions<'a> {
     /// [this example](https://www.typescriptlang.org/play?#code/PTAEBUAsEsGdQPYFcAuBTATqFBPADmqJAIbwB2CoGCCKoZxAtmrHsQMZoA0osl0ddsTKIyAGxygARoQxoAZpjkATbJVKgAVklh0ABgDEaegHQBYAFBoAHngQY6uAqCOUAvKADk8mgFpk6BieANyWNnYO9EwsbJyg0GRkmKAA3pagGaAgEDDwCUlYToRw8WSw0MqExFHMrBzcvPwo8EIUZNBCYjXF8Hr5mCaupumZ4faO+ISuoB7efv1BoRaZWWBQJQvYk-HwKBg4CQDmalQKySiUKJCEAckISVwjGdlSqNjXoEhkAI5IxGLQeTQNCqBjMUCGYw7Uq6NDEVQJQJ4OToVQaOSKORkdhHJ6rd6EMQITpbZyQhB6GHoeGIeQEqg0FC+MRoABuaC69zQ5mWo1s41JhAAQsQsB4UqAfAgAFwuGjBUAAXyWisslmyAEF4NU5LAkGIUDwriVDtB2drBaAlPZpGghDpCHoRRgTFLKSRdts+okBkMeBR9EMeSy6FJRbKFiZnTNUpKaLK5gh-KhMJ4lUt1WAAJJ0-6culXQhFbVyT5kSpYHWM7p1Tg8NmYSRFIgaYRlphSaCHJDIeDyfUSXy-f6A4Gg6I8saRMExeqC+BpXkZKcTZzTWZS5OBEJ4lc12LFH1YRcrFZ75vrrybhY7pen7LrPJHy2tT6wIsfL4drs9nTdCHFoMUIXKcmInIWiAplgXI8qefIRKuwqijGEpSrKgGuAqyp4qqFi4ZmOQlAA7vYADW2rwOEdqosGaChqKABM6GTLAJiRtG4pxjKV5+LcQTpkAA)
     /// of a situation where not having a root namespace can lead to errors.
     pub root_namespace: Option<&'a str>,
+    /// Whether to emit object types with no fields as `Record<string, never>`.
+    ///
+    /// In TypeScript, the type `{}` accepts any non-null value, not just empty
+    /// objects. Setting this to `true` emits `Record<string, never>` instead,
+    /// which more accurately describes the `{}` JSON produced by serializing
+    /// empty structs and struct variants.
+    pub strict_empty_objects: bool,
+    /// The name of a constant listing the names of all emitted types.
+    ///
+    /// If `Some`, a constant with this name is exported from the module after
+    /// all type definitions, e.g.
+    /// `export const allTypeNames = ["Foo", "x.y.Bar"] as const;`. Each name is
+    /// qualified with its namespace path (excluding the root namespace). This
+    /// is the same list as [`Stats::type_names`]. If `None`, no constant is
+    /// emitted.
+    ///
+    /// With [`ModuleSystem::CommonJs`] and a
+    /// [`root_namespace`](Self::root_namespace), the constant is exported
+    /// from the root namespace instead, since the module's export assignment
+    /// cannot be combined with other exports.
+    pub all_type_names: Option<&'a str>,
+    /// The line ending to use in the emitted file.
+    ///
+    /// All newlines written to the output, including those in the
+    /// [`header`](Self::header), are converted to this line ending. The header
+    /// should therefore only use `\n` to separate lines.
+    pub line_ending: LineEnding,
+    /// The module system which the emitted file is used with.
+    ///
+    /// This determines how the root namespace is exported, as described by
+    /// [`ModuleSystem`]. It has no effect if
+    /// [`root_namespace`](Self::root_namespace) is `None`. Imports of
+    /// [`external_imports`](Self::external_imports) are emitted as
+    /// `import type` declarations for either module system, since they are
+    /// erased from the compiled JavaScript.
+    pub module_system: ModuleSystem,
+    /// Whether to emit array types as `readonly` arrays.
+    ///
+    /// If `true`, array types such as those of [`Vec<T>`] and `&'static [T]`
+    /// are emitted as `readonly T[]`, which prevents TypeScript code from
+    /// mutating the deserialized data. Tuple types, including those of
+    /// fixed-size arrays `[T; N]`, are likewise emitted as
+    /// `readonly [A, B, ...]`, and index signatures, including those of maps
+    /// emitted with [`MapStyle::IndexSignature`], as
+    /// `readonly [key:string]:V`. Maps emitted with [`MapStyle::Record`] are
+    /// emitted as `Readonly<Record<K, V>>`.
+    pub readonly_arrays: bool,
+    /// Whether to inline type definitions which are only used once.
+    ///
+    /// If `true`, a defined type which is referenced from exactly one place
+    /// has its definition emitted at that place instead of as a named type.
+    /// The types passed to [`write_definition_file`] and generic types are
+    /// always emitted as named types. Inlined types are not counted in the
+    /// returned [`Stats`], and their docs are emitted before their inlined
+    /// definition.
+    pub inline_single_use: bool,
+    /// Whether to emit a mapped type indexing the variants of each tagged
+    /// union by their discriminant.
+    ///
+    /// If `true`, a union type `Foo` whose members are all objects with the
+    /// same string literal field first (such as enums using
+    /// `#[serde(tag = "type")]`) is followed by
+    /// `export type FooByKind = { [K in Foo["type"]]: Extract<Foo, { "type": K }> };`.
+    /// This requires TypeScript 2.8 or later.
+    pub by_kind_maps: bool,
+    /// Whether to emit a `Partial` alias for each object type.
+    ///
+    /// If `true`, each type definition `Foo` which is an object type (such
+    /// as a struct with named fields) is followed by
+    /// `export type FooPartial = Partial<Foo>;`, which is useful for
+    /// PATCH-style APIs. An intersection (such as from a flattened field) is
+    /// only an object type if all of its members are.
+    pub partial_aliases: bool,
+    /// Whether to emit the types of `f32` and `f64` as branded finite numbers.
+    ///
+    /// JSON cannot represent `NaN` or infinite values and `serde_json` fails
+    /// to serialize them. If `true`, the `F32` and `F64` types are emitted as
+    /// `(number & { readonly "__brand": "Finite" })` with a doc comment
+    /// noting this, so that plain numbers must be explicitly checked before
+    /// being used as them.
+    pub finite_floats: bool,
+    /// Type definitions which are provided by other modules, as pairs of the
+    /// type's qualified name (as in [`Stats::type_names`]) and the module
+    /// path to import it from.
+    ///
+    /// These definitions and the types only they depend on are not emitted.
+    /// Instead, for each one which is referenced,
+    /// `import type { Foo } from "./other";` is emitted after the header and
+    /// the type is referenced by its unqualified name. If several definitions
+    /// have the same unqualified name, each is imported under an alias of its
+    /// qualified name with `.` replaced by `_`, such as
+    /// `import type { Foo as a_Foo } from "./a";`. This requires TypeScript 3.8
+    /// or later.
+    pub external_imports: &'a [(&'a str, &'a str)],
+    /// The name of a type alias for the union of all emitted types.
+    ///
+    /// If `Some`, a type alias with this name is emitted after all type
+    /// definitions, e.g. `export type AnyType = (types.Foo | types.x.y.Bar);`,
+    /// which is useful for values which could be any of the known types.
+    /// Generic types are not included since they have no single type. If
+    /// `None`, no alias is emitted.
+    pub any_type: Option<&'a str>,
+    /// A function which can override the emitted type of a definition.
+    ///
+    /// If `Some`, the function is called with each type definition before it
+    /// is emitted (or inlined), and if it returns `Some`, the returned type
+    /// expression is used as the definition's type instead. For example, this
+    /// can be used to emit `F64` as `(number | null)` for floats which are
+    /// serialized as `null` when they are `NaN`. References to the definition
+    /// are not changed.
+    ///
+    /// The function is called once for every emitted definition and every
+    /// inlined reference, so it should be cheap, e.g. matching on
+    /// [`TypeDefinition::name`].
+    pub type_override: Option<fn(&'static TypeDefinition) -> Option<TypeExpr>>,
+    /// Whether to emit `// prettier-ignore` comments so that
+    /// [Prettier](https://prettier.io/) leaves the emitted code as it is.
+    ///
+    /// If `true`, the comment is emitted before the root namespace, or before
+    /// each top-level type definition if
+    /// [`root_namespace`](Self::root_namespace) is `None`. This is useful for
+    /// checking generated files into a repository which is formatted with
+    /// Prettier without having to format them after each generation.
+    pub prettier_ignore: bool,
+    /// How the [`header`](Self::header) is emitted.
+    ///
+    /// With [`HeaderStyle::Verbatim`], the header must already be formatted as
+    /// TypeScript comments. With the other styles, the header should be plain
+    /// text, and each of its lines is put into a comment.
+    pub header_style: HeaderStyle,
+    /// The maximum number of members of a union type which is emitted inline.
+    ///
+    /// If set, wider unions nested inside a type definition are hoisted into
+    /// a helper type named after the definition, such as `FooUnion1`, which
+    /// is emitted right after it. Very wide unions can slow down the
+    /// TypeScript compiler, which has to check them again wherever they are
+    /// written out, at the cost of extra names in the definition file. Unions
+    /// which make up a whole definition (such as those of enums) are already
+    /// named, so they are never hoisted. Unions in generic definitions are
+    /// also never hoisted.
+    ///
+    /// The default is `None`, which does not limit the width of unions.
+    pub max_union_width: Option<usize>,
+    /// How map types, such as those of `BTreeMap<K, V>` and `HashMap<K, V>`,
+    /// are emitted.
+    pub map_style: MapStyle,
+    /// Whether to start the emitted file with a UTF-8 byte order mark.
+    ///
+    /// If `true`, the character `U+FEFF` (encoded as the bytes `EF BB BF`) is
+    /// written before anything else, for editors and tools which rely on it
+    /// to detect that a file is UTF-8.
+    pub utf8_bom: bool,
+    /// Whether to emit a type alias for the discriminant values of each
+    /// tagged union.
+    ///
+    /// If `true`, a union type `Foo` whose members are all objects with the
+    /// same string literal field first (as with
+    /// [`by_kind_maps`](Self::by_kind_maps)) is followed by
+    /// `export type FooTag = ("A" | "B");`, listing the values of that field.
+    /// This is useful for exhaustive `switch` statements and lookup tables
+    /// over the kinds of a union. The members of the union itself keep their
+    /// literal tag types, since TypeScript can only narrow a union by a
+    /// discriminant whose type is different in each member.
+    pub tag_aliases: bool,
+    /// Documentation of the whole module, emitted as a JSDoc comment with a
+    /// `@packageDocumentation` tag.
+    ///
+    /// If `Some`, the string should be plain text, and each of its lines is
+    /// put into the comment, which is emitted right after the
+    /// [`header`](Self::header). Documentation generators such as
+    /// [TypeDoc](https://typedoc.org/) use it as the documentation of the
+    /// emitted module itself. If `None`, no comment will be added.
+    pub package_docs: Option<&'a str>,
+    /// Whether to emit type definitions which are object types as
+    /// `interface` declarations rather than `type` aliases.
+    ///
+    /// Interfaces can be extended by declaration merging and are sometimes
+    /// preferred by style guides. Definitions which are not object types,
+    /// such as unions, are always emitted as type aliases. This can be
+    /// overridden for a single definition with
+    /// [`TypeDefinition::interface`].
+    pub objects_as_interfaces: bool,
+    /// The name of an ambient module to declare the definitions in.
+    ///
+    /// If `Some`, the whole output (after the [`header`](Self::header) and
+    /// [`package_docs`](Self::package_docs)) is wrapped in
+    /// `declare module "name" { ... }`, so that a `.d.ts` file can provide
+    /// the types of a module with that name, for example to publish them as
+    /// part of a library. Since ambient declarations cannot contain runtime
+    /// values, this should not be used together with
+    /// [`all_type_names`](Self::all_type_names) or the `emit_schema`
+    /// attribute.
+    pub declare_module: Option<&'a str>,
+    /// Whether to emit a type with a handler function for each variant of
+    /// each tagged union.
+    ///
+    /// If `true`, a union type `Foo` whose members are all objects with the
+    /// same string literal field first (as with
+    /// [`by_kind_maps`](Self::by_kind_maps)) is followed by
+    /// ```ts
+    /// export type FooHandlers<R> = {
+    ///     [K in Foo["type"]]: (value: Extract<Foo, { "type": K }>) => R
+    /// };
+    /// ```
+    /// An object of this type must handle every variant of the union, so
+    /// TypeScript reports an error wherever it is written if a variant is
+    /// added in Rust but not handled.
+    pub handler_maps: bool,
+    /// Documentation of the root namespace.
+    ///
+    /// If `Some`, the string should be plain text, and each of its lines is
+    /// put into a JSDoc comment emitted right before the declaration of the
+    /// [`root_namespace`](Self::root_namespace). Since the default export of
+    /// the module is the root namespace, editors also show this
+    /// documentation for the default import. If `None` or if there is no
+    /// root namespace, no comment will be added.
+    pub root_namespace_docs: Option<&'a str>,
+    /// The name and version of the crate which generated the file.
+    ///
+    /// If `Some`, a comment such as `// Generated from my-crate v1.2.3` is
+    /// emitted after the [`header`](Self::header), so that the version of the
+    /// code which produced a definition file can be traced. Since this crate
+    /// cannot know which crate it is used by, the name and version should be
+    /// given as `Some((env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")))`.
+    /// If `None`, no comment is emitted.
+    pub generated_from: Option<(&'a str, &'a str)>,
+}
+
+impl DefinitionFileOptions<'_> {
+    /// Returns whether `def` is provided by one of the
+    /// [`external_imports`](Self::external_imports).
+    fn is_external(&self, def: &TypeDefinition) -> bool {
+        !self.external_imports.is_empty() && {
+            let name = def.qualified_name();
+            self.external_imports
+                .iter()
+                .any(|(external, _)| *external == name)
+        }
+    }
+
+    /// Returns the name an external definition is imported as, given its
+    /// qualified name.
+    ///
+    /// This is its unqualified name, unless another external definition has
+    /// the same unqualified name, in which case it is its qualified name with
+    /// each `.` replaced by `_`.
+    fn external_import_name<'n>(
+        &self,
+        qualified_name: &'n str,
+    ) -> Cow<'n, str> {
+        let name = unqualified_name(qualified_name);
+        if self.external_imports.iter().any(|(external, _)| {
+            *external != qualified_name && unqualified_name(external) == name
+        }) {
+            qualified_name.replace('.', "_").into()
+        } else {
+            name.into()
+        }
+    }
+}
+
+/// Returns the last part of a name qualified with its namespace path.
+fn unqualified_name(qualified_name: &str) -> &str {
+    qualified_name.rsplit('.').next().unwrap_or(qualified_name)
+}
+
+/// The style of line endings used in emitted TypeScript code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix-style line endings (`\n`).
+    Lf,
+    /// Windows-style line endings (`\r\n`).
+    Crlf,
+}
+
+/// The style of comments used to emit the header of a definition file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderStyle {
+    /// The header is emitted as it is.
+    Verbatim,
+    /// Each line of the header is emitted as a line comment (`// ...`).
+    Line,
+    /// The header is emitted as a block comment (`/* ... */`) with each line
+    /// prefixed by ` * `.
+    Block,
+}
+
+/// The style of type used to emit map types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapStyle {
+    /// Maps are emitted using the `Record` utility type, such as
+    /// `Record<string, V>`.
+    Record,
+    /// Maps are emitted as object types without using the `Record` utility
+    /// type, which some style guides do not allow.
+    ///
+    /// Maps with `string` or `number` keys are emitted with an index
+    /// signature, such as `{ [key:string]:V; }`. Maps with other keys, such
+    /// as enums, are emitted as a mapped type, such as `{ [key in K]:V; }`,
+    /// since index signatures cannot have literal key types.
+    IndexSignature,
+}
+
+/// The module system which an emitted definition file is used with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleSystem {
+    /// ECMAScript modules, such as `.mts` files.
+    ///
+    /// The root namespace is exported with `export default types;`.
+    Esm,
+    /// CommonJS modules, such as `.cts` files.
+    ///
+    /// The root namespace is exported with an export assignment
+    /// (`export = types;`), for compatibility with modules consumed without
+    /// `esModuleInterop`. Since TypeScript does not allow other exports in a
+    /// module with an export assignment, the root namespace itself is not
+    /// exported, and the
+    /// [`all_type_names`](DefinitionFileOptions::all_type_names) constant is
+    /// exported from the root namespace.
+    CommonJs,
 }
 
 /// Statistics about the type definitions produced by [`write_definition_file`].
@@ -229,32 +684,187 @@ pub struct DefinitionFileOptions<'a> {
 pub struct Stats {
     /// The number of unique type definitions produced.
     pub type_definitions: usize,
+    /// The names of the type definitions produced, in the order they were
+    /// emitted.
+    ///
+    /// Each name is qualified with its namespace path (excluding the root
+    /// namespace), separated by `.`.
+    pub type_names: Vec<String>,
+}
+
+/// The type definitions emitted by previous calls to
+/// [`write_definition_file_with_shared`].
+///
+/// This can be used to emit type definitions which are common to several
+/// definition files into a single shared file. Definitions which have already
+/// been emitted are not emitted again, and are instead referenced from the
+/// root namespace of the file they were emitted in.
+#[derive(Debug, Clone, Default)]
+pub struct SharedDefinitions {
+    /// The root namespace each definition was emitted under, keyed by the
+    /// definition's hash.
+    defs: BTreeMap<u64, Option<String>>,
+}
+
+impl SharedDefinitions {
+    /// Creates a new empty set of shared definitions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// The hashes of the type definitions in a definition file, as used by
+/// [`write_changed_definitions`].
+///
+/// This can be stored (e.g. serialized as a JSON object) between builds to
+/// detect which definitions have changed since the last build. With the
+/// `serde` crate feature, it implements `Serialize` and `Deserialize`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    /// The hash of each type definition, keyed by its name qualified with its
+    /// namespace path (as in [`Stats::type_names`]).
+    pub type_hashes: BTreeMap<String, u64>,
+}
+
+/// The result of [`write_changed_definitions`].
+#[derive(Debug, Clone)]
+pub struct ManifestDiff {
+    /// Statistics about the type definitions which were written, which are
+    /// those that changed or are new.
+    pub stats: Stats,
+    /// The manifest of all current type definitions, including unchanged
+    /// ones, which should be passed as the previous manifest next time.
+    pub manifest: Manifest,
+    /// The qualified names of the type definitions in the previous manifest
+    /// which no longer exist.
+    pub removed: Vec<String>,
 }
 
 impl<'ctx> EmitCtx<'ctx> {
     fn new(
-        w: &'ctx mut dyn io::Write,
-        root_namespace: Option<&'ctx str>,
+        w: &'ctx mut dyn fmt::Write,
+        options: DefinitionFileOptions<'ctx>,
+        inlined: &'ctx BTreeSet<u64>,
+        shared: &'ctx SharedDefinitions,
     ) -> Self {
         let stats = Stats {
             type_definitions: 0,
+            type_names: Vec::new(),
         };
         Self {
             w,
-            root_namespace,
+            options,
             indent: 0,
             stats,
+            inlined,
+            inline_decisions: BTreeMap::new(),
+            shared,
+            rendered: BTreeMap::new(),
+            hoist_target: None,
+            hoisted: Vec::new(),
+            generated: Vec::new(),
+            name_conflict: None,
+        }
+    }
+
+    /// Returns the root namespace of the shared definition `def` if it was
+    /// emitted by a previous call.
+    fn shared_root_namespace(
+        &self,
+        def: &TypeDefinition,
+    ) -> Option<Option<&'ctx str>> {
+        if self.shared.defs.is_empty() {
+            return None;
+        }
+        self.shared
+            .defs
+            .get(&crate::iter_def_deps::hash_type_def(def))
+            .map(Option::as_deref)
+    }
+}
+
+/// A writer which converts all `\n` characters written to it to the given line
+/// ending.
+struct LineEndingWriter<W> {
+    inner: W,
+    line_ending: LineEnding,
+}
+
+impl<W> fmt::Write for LineEndingWriter<W>
+where
+    W: fmt::Write,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.line_ending {
+            LineEnding::Lf => self.inner.write_str(s),
+            LineEnding::Crlf => {
+                let mut first = true;
+                for line in s.split('\n') {
+                    if !first {
+                        self.inner.write_str("\r\n")?;
+                    }
+                    self.inner.write_str(line)?;
+                    first = false;
+                }
+                Ok(())
+            }
         }
     }
 }
 
+/// A writer which passes all output on to an [`io::Write`].
+///
+/// Since [`fmt::Write`] can't report the cause of an error, the last I/O error
+/// is kept so it can be returned afterwards by [`IoWriter::into_result`].
+#[cfg(feature = "std")]
+struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W> IoWriter<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    fn into_result<T>(self, result: Result<T, WriteError>) -> io::Result<T> {
+        result.map_err(|err| match err {
+            WriteError::Fmt(fmt::Error) => self.error.unwrap_or_else(|| {
+                io::Error::new(io::ErrorKind::Other, "formatter error")
+            }),
+            WriteError::NameConflict(name) => io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "generated type name {:?} conflicts with a type definition",
+                    name
+                ),
+            ),
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W> fmt::Write for IoWriter<W>
+where
+    W: io::Write,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
 struct SepList<'a, T>(&'a [T], &'static str);
 
 impl<'a, T> Emit for SepList<'a, T>
 where
     T: Emit,
 {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self(elements, separator) = self;
         let mut first = true;
         for element in *elements {
@@ -274,7 +884,7 @@ impl<'a, T> Emit for Generics<'a, T>
 where
     T: Emit,
 {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self(args) = self;
         if !args.is_empty() {
             write!(ctx.w, "<")?;
@@ -285,8 +895,34 @@ where
     }
 }
 
+/// The generic variables of a type definition along with their defaults.
+struct GenericVars<'a>(&'a [Ident], &'a [Option<TypeExpr>]);
+
+impl Emit for GenericVars<'_> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
+        let Self(vars, defaults) = self;
+        if !vars.is_empty() {
+            write!(ctx.w, "<")?;
+            let mut first = true;
+            for (i, var) in vars.iter().enumerate() {
+                if !first {
+                    write!(ctx.w, ", ")?;
+                }
+                var.emit(ctx)?;
+                if let Some(Some(default)) = defaults.get(i) {
+                    write!(ctx.w, " = ")?;
+                    default.emit(ctx)?;
+                }
+                first = false;
+            }
+            write!(ctx.w, ">")?;
+        }
+        Ok(())
+    }
+}
+
 impl Emit for TypeExpr {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         match self {
             TypeExpr::Ref(type_info) => ctx.emit_type_ref(type_info),
             TypeExpr::Name(type_name) => type_name.emit(ctx),
@@ -303,12 +939,45 @@ impl Emit for TypeExpr {
 }
 
 impl Emit for TypeName {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self {
             path,
             name,
             generic_args,
         } = self;
+        if let (MapStyle::IndexSignature, [], Ident("Record"), [key, value]) =
+            (ctx.options.map_style, path, name, generic_args)
+        {
+            writeln!(ctx.w, "{{")?;
+            ctx.indent();
+            write!(ctx.w, "{}", ctx.current_indentation())?;
+            if ctx.options.readonly_arrays {
+                write!(ctx.w, "readonly ")?;
+            }
+            // written like the index signatures of object types
+            write!(ctx.w, "[key")?;
+            if let Some(key) = primitive_key(key, 0) {
+                write!(ctx.w, ":{}", key)?;
+            } else {
+                write!(ctx.w, " in ")?;
+                key.emit(ctx)?;
+            }
+            write!(ctx.w, "]:")?;
+            value.emit(ctx)?;
+            writeln!(ctx.w, ";")?;
+            ctx.deindent();
+            write!(ctx.w, "{}}}", ctx.current_indentation())?;
+            return Ok(());
+        }
+        if let (true, [], Ident("Record"), [_, _]) =
+            (ctx.options.readonly_arrays, path, name, generic_args)
+        {
+            write!(ctx.w, "Readonly<")?;
+            name.emit(ctx)?;
+            Generics(generic_args).emit(ctx)?;
+            write!(ctx.w, ">")?;
+            return Ok(());
+        }
         for path_part in *path {
             path_part.emit(ctx)?;
             write!(ctx.w, ".")?;
@@ -320,7 +989,7 @@ impl Emit for TypeName {
 }
 
 impl Emit for TypeString {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self { docs, value } = self;
         docs.emit(ctx)?;
         write!(ctx.w, "{:?}", value)?;
@@ -329,9 +998,12 @@ impl Emit for TypeString {
 }
 
 impl Emit for TypeTuple {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self { docs, elements } = self;
         docs.emit(ctx)?;
+        if ctx.options.readonly_arrays {
+            write!(ctx.w, "readonly ")?;
+        }
         write!(ctx.w, "[")?;
         SepList(elements, ", ").emit(ctx)?;
         write!(ctx.w, "]")?;
@@ -340,7 +1012,7 @@ impl Emit for TypeTuple {
 }
 
 impl Emit for TypeObject {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self {
             docs,
             index_signature,
@@ -350,44 +1022,69 @@ impl Emit for TypeObject {
             docs.emit(ctx)?;
             write!(ctx.w, "{}", ctx.current_indentation())?;
         }
+        if ctx.options.strict_empty_objects
+            && index_signature.is_none()
+            && fields.is_empty()
+        {
+            write!(ctx.w, "Record<string, never>")?;
+            return Ok(());
+        }
         writeln!(ctx.w, "{{")?;
         ctx.indent();
         if let Some(IndexSignature { docs, name, value }) = index_signature {
             docs.emit(ctx)?;
-            write!(ctx.w, "{}[", ctx.current_indentation())?;
+            write!(ctx.w, "{}", ctx.current_indentation())?;
+            if ctx.options.readonly_arrays {
+                write!(ctx.w, "readonly ")?;
+            }
+            write!(ctx.w, "[")?;
             name.emit(ctx)?;
             write!(ctx.w, ":string]:")?;
             value.emit(ctx)?;
             write!(ctx.w, ";")?;
             writeln!(ctx.w)?;
         }
-        for ObjectField {
+        for field in *fields {
+            field.emit(ctx)?;
+        }
+        ctx.deindent();
+        write!(ctx.w, "{}}}", ctx.current_indentation())?;
+        Ok(())
+    }
+}
+
+impl Emit for ObjectField {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
+        let Self {
             docs,
             name,
             optional,
+            readonly,
             r#type,
-        } in *fields
-        {
-            docs.emit(ctx)?;
-            write!(ctx.w, "{}", ctx.current_indentation())?;
-            name.emit(ctx)?;
-            if *optional {
-                write!(ctx.w, "?")?;
-            }
-            write!(ctx.w, ": ")?;
-            r#type.emit(ctx)?;
-            writeln!(ctx.w, ";")?;
+        } = self;
+        docs.emit(ctx)?;
+        write!(ctx.w, "{}", ctx.current_indentation())?;
+        if *readonly {
+            write!(ctx.w, "readonly ")?;
         }
-        ctx.deindent();
-        write!(ctx.w, "{}}}", ctx.current_indentation())?;
+        name.emit(ctx)?;
+        if *optional {
+            write!(ctx.w, "?")?;
+        }
+        write!(ctx.w, ": ")?;
+        r#type.emit(ctx)?;
+        writeln!(ctx.w, ";")?;
         Ok(())
     }
 }
 
 impl Emit for TypeArray {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self { docs, item } = self;
         docs.emit(ctx)?;
+        if ctx.options.readonly_arrays {
+            write!(ctx.w, "readonly ")?;
+        }
         write!(ctx.w, "(")?;
         item.emit(ctx)?;
         write!(ctx.w, ")[]")?;
@@ -396,7 +1093,26 @@ impl Emit for TypeArray {
 }
 
 impl Emit for TypeUnion {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
+        if ctx
+            .options
+            .max_union_width
+            .map_or(false, |max_union_width| {
+                self.members.len() > max_union_width
+            })
+        {
+            if let Some(type_def) = ctx.hoist_target {
+                ctx.hoisted.push(*self);
+                let index = ctx.hoisted.len();
+                return ctx.emit_hoisted_union_ref(type_def, index);
+            }
+        }
+        self.emit_inline(ctx)
+    }
+}
+
+impl TypeUnion {
+    fn emit_inline(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self { docs, members } = self;
         docs.emit(ctx)?;
         if members.is_empty() {
@@ -411,10 +1127,54 @@ impl Emit for TypeUnion {
 }
 
 impl Emit for TypeIntersection {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self { docs, members } = self;
         docs.emit(ctx)?;
-        if members.is_empty() {
+        if let Some((value, fields)) = flattened_map(members) {
+            // an object type with a flattened map is emitted as a single
+            // object with an index signature, since intersecting it with the
+            // map's `Record` type would require every field to also have the
+            // type of the map's values
+            let mut values = Vec::<&'static TypeExpr>::new();
+            let mut any_optional = false;
+            for value in core::iter::once(value)
+                .chain(fields.iter().map(|field| &field.r#type))
+            {
+                let hash = crate::iter_def_deps::hash_type_expr_visit(value);
+                if !values.iter().any(|other| {
+                    crate::iter_def_deps::hash_type_expr_visit(other) == hash
+                }) {
+                    values.push(value);
+                }
+            }
+            for field in fields {
+                any_optional |= field.optional;
+            }
+            writeln!(ctx.w, "{{")?;
+            ctx.indent();
+            write!(ctx.w, "{}", ctx.current_indentation())?;
+            if ctx.options.readonly_arrays {
+                write!(ctx.w, "readonly ")?;
+            }
+            write!(ctx.w, "[key:string]:")?;
+            if values.len() == 1 && !any_optional {
+                value.emit(ctx)?;
+            } else {
+                // the index signature must be a supertype of every field
+                write!(ctx.w, "(")?;
+                SepList(&values, " | ").emit(ctx)?;
+                if any_optional {
+                    write!(ctx.w, " | undefined")?;
+                }
+                write!(ctx.w, ")")?;
+            }
+            writeln!(ctx.w, ";")?;
+            for field in fields {
+                field.emit(ctx)?;
+            }
+            ctx.deindent();
+            write!(ctx.w, "{}}}", ctx.current_indentation())?;
+        } else if members.is_empty() {
             write!(ctx.w, "unknown")?;
         } else {
             write!(ctx.w, "(")?;
@@ -426,7 +1186,7 @@ impl Emit for TypeIntersection {
 }
 
 impl Emit for Ident {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self(name) = self;
         write!(ctx.w, "{}", name)?;
         Ok(())
@@ -434,7 +1194,7 @@ impl Emit for Ident {
 }
 
 impl Emit for Docs {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         let Self(docs) = self;
         writeln!(ctx.w)?;
         writeln!(ctx.w, "{}/**", ctx.current_indentation())?;
@@ -450,7 +1210,7 @@ impl<T> Emit for &T
 where
     T: Emit,
 {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         T::emit(self, ctx)
     }
 }
@@ -459,7 +1219,7 @@ impl<T> Emit for Option<T>
 where
     T: Emit,
 {
-    fn emit(&self, ctx: &mut EmitCtx<'_>) -> io::Result<()> {
+    fn emit(&self, ctx: &mut EmitCtx<'_>) -> fmt::Result {
         if let Some(inner) = self {
             inner.emit(ctx)
         } else {
@@ -469,58 +1229,468 @@ where
 }
 
 impl EmitCtx<'_> {
-    fn emit_type_def(&mut self, infos: &[&'static TypeInfo]) -> io::Result<()> {
-        for TypeDefinition {
+    fn emit_type_def(&mut self, infos: &[&'static TypeInfo]) -> fmt::Result {
+        let options = self.options;
+        let is_external = |def: &TypeDefinition| options.is_external(def);
+        let type_defs = crate::iter_def_deps::IterDefDeps::with_external(
+            infos,
+            &is_external,
+        )
+        .filter(|type_def| {
+            !self.is_inlined(type_def)
+                && self.shared_root_namespace(type_def).is_none()
+                && !is_external(type_def)
+        })
+        .collect::<Vec<_>>();
+        for type_def in &type_defs {
+            self.stats.type_definitions += 1;
+            self.stats.type_names.push(type_def.qualified_name());
+        }
+        let any_type_members = self.options.any_type.map(|_| {
+            type_defs
+                .iter()
+                .filter(|type_def| type_def.generic_vars.is_empty())
+                .map(|type_def| {
+                    let name = type_def.qualified_name();
+                    if let Some(root_namespace) = self.options.root_namespace {
+                        format!("{}.{}", root_namespace, name)
+                    } else {
+                        name
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+        #[cfg(not(feature = "rayon"))]
+        for type_def in type_defs {
+            self.emit_single_type_def(type_def)?;
+        }
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+
+            // render each definition into its own buffer in parallel, then
+            // write them out in the original order
+            let Self {
+                options,
+                indent,
+                inlined,
+                shared,
+                ..
+            } = *self;
+            let bufs = type_defs
+                .into_par_iter()
+                .map(|type_def| {
+                    let mut buf = String::new();
+                    let mut ctx =
+                        EmitCtx::new(&mut buf, options, inlined, shared);
+                    ctx.indent = indent;
+                    ctx.emit_single_type_def(type_def)?;
+                    let generated = ctx.generated;
+                    Ok((buf, generated))
+                })
+                .collect::<Result<Vec<_>, fmt::Error>>()?;
+            for (buf, generated) in bufs {
+                self.w.write_str(&buf)?;
+                self.generated.extend(generated);
+            }
+        }
+        // generated aliases must not shadow any of the definitions
+        let type_names = self.stats.type_names.iter().collect::<BTreeSet<_>>();
+        if let Some(name) =
+            self.generated.iter().find(|name| type_names.contains(name))
+        {
+            self.name_conflict = Some(name.clone());
+            return Err(fmt::Error);
+        }
+        if let (Some(any_type), Some(members)) =
+            (self.options.any_type, any_type_members)
+        {
+            write!(
+                self.w,
+                "{}export type {} = ",
+                self.current_indentation(),
+                any_type
+            )?;
+            if members.is_empty() {
+                write!(self.w, "never")?;
+            } else {
+                write!(self.w, "({})", members.join(" | "))?;
+            }
+            writeln!(self.w, ";")?;
+        }
+        Ok(())
+    }
+
+    /// Records the name of an alias generated for a definition, which is the
+    /// definition's name followed by `suffix`.
+    fn add_generated_name(&mut self, type_def: &TypeDefinition, suffix: &str) {
+        self.generated
+            .push(format!("{}{}", type_def.qualified_name(), suffix));
+    }
+
+    fn emit_single_type_def(
+        &mut self,
+        type_def: &'static TypeDefinition,
+    ) -> fmt::Result {
+        let TypeDefinition {
             docs,
             path,
             name,
             generic_vars,
+            generic_defaults,
             def,
-        } in crate::iter_def_deps::IterDefDeps::new(infos)
-        {
-            self.stats.type_definitions += 1;
-            if !path.is_empty() {
+            emit_schema,
+            interface,
+        } = type_def;
+        let finite = self.options.finite_floats && is_float_def(type_def);
+        if self.options.max_union_width.is_some() && generic_vars.is_empty() {
+            self.hoist_target = Some(type_def);
+        }
+        let prettier_ignore = self.options.prettier_ignore && self.indent == 0;
+        if prettier_ignore && !path.is_empty() {
+            writeln!(self.w, "// prettier-ignore")?;
+        }
+        if !path.is_empty() {
+            write!(self.w, "{}export namespace ", self.current_indentation())?;
+            SepList(path, ".").emit(self)?;
+            writeln!(self.w, " {{")?;
+            self.indent();
+        }
+        if finite {
+            Some(FINITE_DOCS).emit(self)?;
+        } else {
+            docs.emit(self)?;
+        }
+        if prettier_ignore && path.is_empty() {
+            writeln!(self.w, "// prettier-ignore")?;
+        }
+        match self.def_body(type_def) {
+            TypeExpr::Object(type_object)
+                if interface.unwrap_or(self.options.objects_as_interfaces)
+                    && !(self.options.strict_empty_objects
+                        && type_object.index_signature.is_none()
+                        && type_object.fields.is_empty()) =>
+            {
                 write!(
                     self.w,
-                    "{}export namespace ",
+                    "{}export interface ",
                     self.current_indentation()
                 )?;
-                SepList(path, ".").emit(self)?;
-                writeln!(self.w, " {{")?;
-                self.indent();
+                name.emit(self)?;
+                GenericVars(generic_vars, generic_defaults).emit(self)?;
+                write!(self.w, " ")?;
+                type_object.emit(self)?;
             }
-            docs.emit(self)?;
+            def => {
+                write!(self.w, "{}export type ", self.current_indentation())?;
+                name.emit(self)?;
+                GenericVars(generic_vars, generic_defaults).emit(self)?;
+                write!(self.w, " = ")?;
+                match def {
+                    TypeExpr::Union(type_union) => {
+                        type_union.emit_inline(self)?
+                    }
+                    def => def.emit(self)?,
+                }
+                write!(self.w, ";")?;
+            }
+        }
+        if self.options.by_kind_maps {
+            if let Some(tag) = union_tag(def) {
+                self.add_generated_name(type_def, "ByKind");
+                let key = unused_generic_var(generic_vars, "K");
+                writeln!(self.w)?;
+                write!(self.w, "{}export type ", self.current_indentation())?;
+                name.emit(self)?;
+                write!(self.w, "ByKind")?;
+                GenericVars(generic_vars, generic_defaults).emit(self)?;
+                write!(self.w, " = {{ [{} in ", key)?;
+                name.emit(self)?;
+                Generics(generic_vars).emit(self)?;
+                write!(self.w, "[{:?}]]: Extract<", tag)?;
+                name.emit(self)?;
+                Generics(generic_vars).emit(self)?;
+                write!(self.w, ", {{ {:?}: {} }}> }};", tag, key)?;
+            }
+        }
+        if self.options.tag_aliases {
+            if let Some(values) = union_tag_values(def) {
+                self.add_generated_name(type_def, "Tag");
+                writeln!(self.w)?;
+                write!(self.w, "{}export type ", self.current_indentation())?;
+                name.emit(self)?;
+                write!(self.w, "Tag = (")?;
+                SepList(&values, " | ").emit(self)?;
+                write!(self.w, ");")?;
+            }
+        }
+        if self.options.handler_maps {
+            if let Some(tag) = union_tag(def) {
+                self.add_generated_name(type_def, "Handlers");
+                // the result type comes first since it has no default
+                let result = unused_generic_var(generic_vars, "R");
+                let key = unused_generic_var(generic_vars, "K");
+                writeln!(self.w)?;
+                write!(self.w, "{}export type ", self.current_indentation())?;
+                name.emit(self)?;
+                write!(self.w, "Handlers<{}", result)?;
+                for (i, var) in generic_vars.iter().enumerate() {
+                    write!(self.w, ", ")?;
+                    var.emit(self)?;
+                    if let Some(Some(default)) = generic_defaults.get(i) {
+                        write!(self.w, " = ")?;
+                        default.emit(self)?;
+                    }
+                }
+                write!(self.w, "> = {{ [{} in ", key)?;
+                name.emit(self)?;
+                Generics(generic_vars).emit(self)?;
+                write!(self.w, "[{:?}]]: (value: Extract<", tag)?;
+                name.emit(self)?;
+                Generics(generic_vars).emit(self)?;
+                write!(
+                    self.w,
+                    ", {{ {:?}: {} }}>) => {} }};",
+                    tag, key, result
+                )?;
+            }
+        }
+        if self.options.partial_aliases
+            && matches!(def, TypeExpr::Object(_) | TypeExpr::Intersection(_))
+            && is_object_type(def, 0)
+        {
+            self.add_generated_name(type_def, "Partial");
+            writeln!(self.w)?;
             write!(self.w, "{}export type ", self.current_indentation())?;
             name.emit(self)?;
+            write!(self.w, "Partial")?;
+            GenericVars(generic_vars, generic_defaults).emit(self)?;
+            write!(self.w, " = Partial<")?;
+            name.emit(self)?;
             Generics(generic_vars).emit(self)?;
-            write!(self.w, " = ")?;
-            def.emit(self)?;
-            write!(self.w, ";")?;
-            if !path.is_empty() {
-                writeln!(self.w)?;
-                self.deindent();
-                write!(self.w, "{}}}", self.current_indentation())?;
+            write!(self.w, ">;")?;
+        }
+        if *emit_schema {
+            self.add_generated_name(type_def, "Schema");
+            let mut fields = Vec::new();
+            self.schema_fields(&self.def_body(type_def), 0, &mut fields);
+            writeln!(self.w)?;
+            write!(self.w, "{}export const ", self.current_indentation())?;
+            name.emit(self)?;
+            writeln!(self.w, "Schema = {{")?;
+            self.indent();
+            for field in fields {
+                writeln!(
+                    self.w,
+                    "{}{:?}: {:?},",
+                    self.current_indentation(),
+                    field.name.value,
+                    self.schema_kind(&field.r#type, 0),
+                )?;
             }
+            self.deindent();
+            write!(self.w, "{}}} as const;", self.current_indentation())?;
+        }
+        // hoisted unions may hoist further unions of their own
+        let mut index = 0;
+        while let Some(type_union) = self.hoisted.get(index).copied() {
+            index += 1;
+            self.add_generated_name(type_def, &format!("Union{}", index));
+            writeln!(self.w)?;
+            write!(self.w, "{}export type ", self.current_indentation())?;
+            name.emit(self)?;
+            write!(self.w, "Union{} = ", index)?;
+            type_union.emit_inline(self)?;
+            write!(self.w, ";")?;
+        }
+        self.hoisted.clear();
+        self.hoist_target = None;
+        if !path.is_empty() {
             writeln!(self.w)?;
+            self.deindent();
+            write!(self.w, "{}}}", self.current_indentation())?;
         }
+        writeln!(self.w)?;
         Ok(())
     }
 
-    fn emit_type_ref(&mut self, info: &'static TypeInfo) -> io::Result<()> {
+    fn emit_type_ref(&mut self, info: &'static TypeInfo) -> fmt::Result {
+        // type infos are static and the same type is often referenced many
+        // times, so each rendering is cached by the type info's address, but
+        // separately for each definition since it may hoist unions out
+        let hoist_target = self
+            .hoist_target
+            .map_or(0, |type_def| type_def as *const TypeDefinition as usize);
+        let key = (info as *const TypeInfo as usize, self.indent, hoist_target);
+        if let Some(rendered) = self.rendered.get(&key) {
+            return self.w.write_str(rendered);
+        }
+        let mut buf = String::new();
+        let mut ctx =
+            EmitCtx::new(&mut buf, self.options, self.inlined, self.shared);
+        ctx.indent = self.indent;
+        ctx.rendered = core::mem::take(&mut self.rendered);
+        ctx.hoist_target = self.hoist_target;
+        ctx.hoisted = core::mem::take(&mut self.hoisted);
+        let result = ctx.emit_type_ref_uncached(info);
+        self.rendered = ctx.rendered;
+        self.hoisted = ctx.hoisted;
+        result?;
+        self.w.write_str(&buf)?;
+        self.rendered.insert(key, buf);
+        Ok(())
+    }
+
+    /// Returns the type of a definition, applying any overrides from the
+    /// options.
+    fn def_body(&self, type_def: &'static TypeDefinition) -> TypeExpr {
+        if let Some(def) = self
+            .options
+            .type_override
+            .and_then(|type_override| type_override(type_def))
+        {
+            def
+        } else if self.options.finite_floats && is_float_def(type_def) {
+            FINITE_NUMBER
+        } else {
+            type_def.def
+        }
+    }
+
+    /// Collects the fields of an object type, including the fields of any
+    /// flattened types.
+    fn schema_fields(
+        &self,
+        expr: &TypeExpr,
+        depth: usize,
+        fields: &mut Vec<&'static ObjectField>,
+    ) {
+        match expr {
+            TypeExpr::Object(TypeObject {
+                fields: object_fields,
+                ..
+            }) => {
+                fields.extend(object_fields.iter());
+            }
+            TypeExpr::Intersection(TypeIntersection { docs: _, members }) => {
+                for member in *members {
+                    self.schema_fields(member, depth, fields);
+                }
+            }
+            TypeExpr::Ref(TypeInfo::Defined(DefinedTypeInfo {
+                def, ..
+            })) if depth < MAX_DEPTH => {
+                self.schema_fields(&self.def_body(def), depth + 1, fields);
+            }
+            _ => {}
+        }
+    }
+
+    /// Finds the kind of value described by a type expression, as emitted in the
+    /// schema constant of a definition.
+    fn schema_kind(&self, expr: &TypeExpr, depth: usize) -> &'static str {
+        if depth >= MAX_DEPTH {
+            return "unknown";
+        }
+        match expr {
+            TypeExpr::Ref(TypeInfo::Native(NativeTypeInfo { r#ref })) => {
+                self.schema_kind(r#ref, depth + 1)
+            }
+            TypeExpr::Ref(TypeInfo::Defined(DefinedTypeInfo {
+                def, ..
+            })) => self.schema_kind(&self.def_body(def), depth + 1),
+            TypeExpr::Name(TypeName {
+                path: [],
+                name: Ident(name),
+                generic_args: _,
+            }) => match *name {
+                "string" | "number" | "boolean" | "null" => name,
+                "Record" => "object",
+                _ => "unknown",
+            },
+            TypeExpr::Name(_) => "unknown",
+            TypeExpr::String(_) => "string",
+            TypeExpr::Tuple(_) | TypeExpr::Array(_) => "array",
+            TypeExpr::Object(_) | TypeExpr::Intersection(_) => "object",
+            TypeExpr::Union(TypeUnion { docs: _, members }) => {
+                // null members are ignored so that optional values have the kind
+                // of their inner value
+                let mut kinds = members
+                    .iter()
+                    .map(|member| self.schema_kind(member, depth + 1))
+                    .filter(|kind| *kind != "null");
+                match kinds.next() {
+                    Some(kind) if kinds.all(|other| other == kind) => kind,
+                    Some(_) => "union",
+                    None => "null",
+                }
+            }
+        }
+    }
+
+    /// Emits a reference to the union with the given (1-based) index which
+    /// was hoisted out of a definition.
+    fn emit_hoisted_union_ref(
+        &mut self,
+        type_def: &'static TypeDefinition,
+        index: usize,
+    ) -> fmt::Result {
+        if let Some(root_namespace) = self.options.root_namespace {
+            write!(self.w, "{}.", root_namespace)?;
+        }
+        for path_part in type_def.path {
+            path_part.emit(self)?;
+            write!(self.w, ".")?;
+        }
+        type_def.name.emit(self)?;
+        write!(self.w, "Union{}", index)?;
+        Ok(())
+    }
+
+    fn emit_type_ref_uncached(
+        &mut self,
+        info: &'static TypeInfo,
+    ) -> fmt::Result {
         match info {
             TypeInfo::Native(NativeTypeInfo { r#ref }) => r#ref.emit(self),
+            TypeInfo::Defined(DefinedTypeInfo { def, .. })
+                if self.is_inlined(def) =>
+            {
+                // the docs of the definition are kept with its body
+                if let Some(docs) = def.docs {
+                    docs.emit(self)?;
+                    write!(self.w, "{}", self.current_indentation())?;
+                }
+                self.def_body(def).emit(self)
+            }
+            TypeInfo::Defined(DefinedTypeInfo { def, generic_args })
+                if self.options.is_external(def) =>
+            {
+                // external definitions are referenced by their imported name
+                let name = def.qualified_name();
+                write!(self.w, "{}", self.options.external_import_name(&name))?;
+                Generics(generic_args).emit(self)
+            }
             TypeInfo::Defined(DefinedTypeInfo {
-                def:
-                    TypeDefinition {
-                        docs: _,
-                        path,
-                        name,
-                        generic_vars: _,
-                        def: _,
-                    },
+                def: type_def,
                 generic_args,
             }) => {
-                if let Some(root_namespace) = self.root_namespace {
+                let TypeDefinition {
+                    docs: _,
+                    path,
+                    name,
+                    generic_vars: _,
+                    generic_defaults: _,
+                    def: _,
+                    emit_schema: _,
+                    interface: _,
+                } = type_def;
+                // shared definitions are referenced from the namespace they
+                // were emitted under
+                let root_namespace = self
+                    .shared_root_namespace(type_def)
+                    .unwrap_or(self.options.root_namespace);
+                if let Some(root_namespace) = root_namespace {
                     write!(self.w, "{}.", root_namespace)?;
                 }
                 for path_part in *path {
@@ -540,6 +1710,30 @@ impl Default for DefinitionFileOptions<'_> {
         Self {
             header: Some("// AUTO-GENERATED by typescript-type-def\n"),
             root_namespace: Some("types"),
+            strict_empty_objects: false,
+            all_type_names: None,
+            line_ending: LineEnding::Lf,
+            module_system: ModuleSystem::Esm,
+            readonly_arrays: false,
+            inline_single_use: false,
+            by_kind_maps: false,
+            partial_aliases: false,
+            finite_floats: false,
+            external_imports: &[],
+            any_type: None,
+            type_override: None,
+            prettier_ignore: false,
+            header_style: HeaderStyle::Verbatim,
+            max_union_width: None,
+            map_style: MapStyle::Record,
+            utf8_bom: false,
+            tag_aliases: false,
+            package_docs: None,
+            objects_as_interfaces: false,
+            declare_module: None,
+            handler_maps: false,
+            root_namespace_docs: None,
+            generated_from: None,
         }
     }
 }
@@ -566,6 +1760,18 @@ impl Default for DefinitionFileOptions<'_> {
 /// Note that the TypeScript code generated by this library is not very
 /// human-readable. To make the code human-readable, use a TypeScript code
 /// formatter (such as [Prettier](https://prettier.io/)) on the output.
+///
+/// # Errors
+///
+/// Besides errors from the writer, an error of kind
+/// [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if an alias
+/// generated for a definition, such as `FooPartial` with
+/// [`partial_aliases`](DefinitionFileOptions::partial_aliases) or the
+/// `FooUnion1` of a union hoisted with
+/// [`max_union_width`](DefinitionFileOptions::max_union_width) or the
+/// `FooSchema` constant of the `emit_schema` attribute, has the same name as
+/// another definition.
+#[cfg(feature = "std")]
 pub fn write_definition_file<W, T: ?Sized>(
     writer: W,
     options: DefinitionFileOptions<'_>,
@@ -577,6 +1783,133 @@ where
     write_definition_file_from_type_infos(writer, options, &[&T::INFO])
 }
 
+/// Writes a TypeScript definition file containing type definitions for `T` to
+/// the given [`fmt::Write`] writer.
+///
+/// This is the same as [`write_definition_file`], but is more convenient when
+/// emitting directly into a [`String`]. Unlike [`write_definition_file`], this
+/// is also available without the `std` feature.
+///
+/// # Errors
+///
+/// Since [`fmt::Error`] carries no message, a generated alias with the same
+/// name as another definition (as described for [`write_definition_file`])
+/// is returned as a bare [`fmt::Error`], just like an error from the writer.
+///
+/// # Example
+/// ```
+/// use typescript_type_def::{
+///     write_definition_file_fmt, DefinitionFileOptions, TypeDef,
+/// };
+///
+/// #[derive(TypeDef)]
+/// struct Foo {
+///     a: String,
+/// }
+///
+/// let mut ts = String::new();
+/// write_definition_file_fmt::<_, Foo>(
+///     &mut ts,
+///     DefinitionFileOptions::default(),
+/// )
+/// .unwrap();
+/// assert!(ts.contains("export type Foo = {"));
+/// ```
+pub fn write_definition_file_fmt<W, T: ?Sized>(
+    writer: W,
+    options: DefinitionFileOptions<'_>,
+) -> Result<Stats, fmt::Error>
+where
+    W: fmt::Write,
+    T: TypeDef,
+{
+    let (stats, _) = write_definition_file_impl(
+        writer,
+        options,
+        &[&T::INFO],
+        &SharedDefinitions::new(),
+    )
+    .map_err(|_| fmt::Error)?;
+    Ok(stats)
+}
+
+/// Writes just the type definitions for `T` and its dependencies to the given
+/// writer.
+///
+/// This is the same as [`write_definition_file`] with no
+/// [`header`](DefinitionFileOptions::header) and no
+/// [`root_namespace`](DefinitionFileOptions::root_namespace), which is useful
+/// for embedding a type into an existing TypeScript module or documentation.
+/// Types are referenced by their unqualified names, so see the docs of
+/// [`root_namespace`](DefinitionFileOptions::root_namespace) for a note about
+/// name collisions.
+///
+/// # Example
+/// ```
+/// use typescript_type_def::{write_type_definition, TypeDef};
+///
+/// #[derive(TypeDef)]
+/// struct Foo {
+///     a: Vec<String>,
+/// }
+///
+/// let mut ts = Vec::new();
+/// write_type_definition::<_, Foo>(&mut ts).unwrap();
+/// assert_eq!(
+///     String::from_utf8(ts).unwrap(),
+///     "export type Foo = {\n    \"a\": (string)[];\n};\n",
+/// );
+/// ```
+#[cfg(feature = "std")]
+pub fn write_type_definition<W, T: ?Sized>(writer: W) -> io::Result<Stats>
+where
+    W: io::Write,
+    T: TypeDef,
+{
+    write_definition_file::<_, T>(
+        writer,
+        DefinitionFileOptions {
+            header: None,
+            root_namespace: None,
+            ..Default::default()
+        },
+    )
+}
+
+/// Returns a hash of the type definitions of `T` and its dependencies.
+///
+/// The hash changes whenever anything about these definitions which affects
+/// the emitted TypeScript code changes, such as a field, a name, or a doc
+/// comment, so build scripts can store it and skip writing the definition
+/// file again when it is unchanged. It does not depend on the
+/// [`DefinitionFileOptions`] used to write the file. The hash is
+/// deterministic, so it is the same between builds and platforms, but it may
+/// change between versions of this crate.
+///
+/// # Example
+/// ```
+/// use typescript_type_def::{schema_hash, TypeDef};
+///
+/// #[derive(TypeDef)]
+/// struct Foo {
+///     a: Vec<String>,
+/// }
+///
+/// #[derive(TypeDef)]
+/// struct Bar {
+///     a: Vec<u8>,
+/// }
+///
+/// assert_eq!(schema_hash::<Foo>(), schema_hash::<Foo>());
+/// assert_ne!(schema_hash::<Foo>(), schema_hash::<Bar>());
+/// ```
+pub fn schema_hash<T: ?Sized>() -> u64
+where
+    T: TypeDef,
+{
+    crate::iter_def_deps::hash_schema(&[&T::INFO])
+}
+
 /// Writes a TypeScript definition file containing type definitions for the
 /// given list of type info values to the given writer.
 ///
@@ -601,30 +1934,676 @@ where
 /// Note that the TypeScript code generated by this library is not very
 /// human-readable. To make the code human-readable, use a TypeScript code
 /// formatter (such as [Prettier](https://prettier.io/)) on the output.
+#[cfg(feature = "std")]
 pub fn write_definition_file_from_type_infos<W>(
-    mut writer: W,
+    writer: W,
+    options: DefinitionFileOptions<'_>,
+    type_infos: &[&'static TypeInfo],
+) -> io::Result<Stats>
+where
+    W: io::Write,
+{
+    let mut writer = IoWriter::new(writer);
+    let result = write_definition_file_impl(
+        &mut writer,
+        options,
+        type_infos,
+        &SharedDefinitions::new(),
+    );
+    let (stats, _) = writer.into_result(result)?;
+    Ok(stats)
+}
+
+/// Writes a TypeScript definition file containing type definitions for the
+/// given list of type info values to the given writer, skipping definitions
+/// which were already emitted by a previous call with the same `shared`
+/// definitions.
+///
+/// Each type definition emitted is added to `shared`. References to
+/// definitions emitted by a previous call use the
+/// [`root_namespace`](DefinitionFileOptions::root_namespace) of that call, so
+/// the file should import that namespace, e.g. using the
+/// [`header`](DefinitionFileOptions::header) option.
+///
+/// # Example
+/// ```
+/// use typescript_type_def::{
+///     write_definition_file_with_shared, DefinitionFileOptions,
+///     SharedDefinitions, TypeDef,
+/// };
+///
+/// #[derive(TypeDef)]
+/// struct Common {
+///     a: String,
+/// }
+///
+/// #[derive(TypeDef)]
+/// struct Foo {
+///     common: Common,
+/// }
+///
+/// let mut shared = SharedDefinitions::new();
+/// let mut common_ts = Vec::new();
+/// write_definition_file_with_shared(
+///     &mut common_ts,
+///     DefinitionFileOptions {
+///         root_namespace: Some("common"),
+///         ..Default::default()
+///     },
+///     &[&Common::INFO],
+///     &mut shared,
+/// )
+/// .unwrap();
+/// let mut foo_ts = Vec::new();
+/// write_definition_file_with_shared(
+///     &mut foo_ts,
+///     DefinitionFileOptions {
+///         header: Some("import common from \"./common\";"),
+///         ..Default::default()
+///     },
+///     &[&Foo::INFO],
+///     &mut shared,
+/// )
+/// .unwrap();
+/// let foo_ts = String::from_utf8(foo_ts).unwrap();
+/// assert!(foo_ts.contains(r#""common": common.Common;"#));
+/// assert!(!foo_ts.contains("export type Common"));
+/// ```
+#[cfg(feature = "std")]
+pub fn write_definition_file_with_shared<W>(
+    writer: W,
     options: DefinitionFileOptions<'_>,
     type_infos: &[&'static TypeInfo],
+    shared: &mut SharedDefinitions,
 ) -> io::Result<Stats>
 where
     W: io::Write,
 {
-    let mut ctx = EmitCtx::new(&mut writer, options.root_namespace);
+    use crate::iter_def_deps::{hash_type_def, IterDefDeps};
+
+    let mut writer = IoWriter::new(writer);
+    let result =
+        write_definition_file_impl(&mut writer, options, type_infos, shared);
+    let (stats, inlined) = writer.into_result(result)?;
+    let root_namespace = options.root_namespace.map(ToOwned::to_owned);
+    let is_external = |def: &TypeDefinition| options.is_external(def);
+    for type_def in IterDefDeps::with_external(type_infos, &is_external) {
+        let hash = hash_type_def(type_def);
+        if !inlined.contains(&hash) && !is_external(type_def) {
+            shared
+                .defs
+                .entry(hash)
+                .or_insert_with(|| root_namespace.clone());
+        }
+    }
+    Ok(stats)
+}
+
+/// Writes a TypeScript definition file containing only the type definitions
+/// for the given list of type info values which have changed since the
+/// previous manifest.
+///
+/// This is the same as [`write_definition_file_from_type_infos`], except that
+/// definitions whose hash (see [`schema_hash`]) is the same as in `previous`
+/// are not emitted. They are still referenced from the root namespace as
+/// usual, so the written file is meant to update a larger definition file
+/// which already contains the unchanged definitions, such as by replacing the
+/// changed definitions and deleting the removed ones. This is useful for very
+/// large schemas where regenerating everything is slow.
+///
+/// Since definitions are compared one at a time,
+/// [`inline_single_use`](DefinitionFileOptions::inline_single_use) is not
+/// supported and is ignored. The hashes also do not depend on the options, so
+/// after changing the options, all definitions should be written again by
+/// passing an empty manifest.
+///
+/// # Example
+/// ```
+/// use typescript_type_def::{
+///     write_changed_definitions, DefinitionFileOptions, Manifest, TypeDef,
+/// };
+///
+/// #[derive(TypeDef)]
+/// struct Foo {
+///     a: String,
+/// }
+///
+/// let mut buf = Vec::new();
+/// let diff = write_changed_definitions(
+///     &mut buf,
+///     Default::default(),
+///     &[&Foo::INFO],
+///     &Manifest::default(),
+/// )
+/// .unwrap();
+/// assert_eq!(diff.stats.type_names, ["Foo"]);
+///
+/// let mut buf = Vec::new();
+/// let diff = write_changed_definitions(
+///     &mut buf,
+///     Default::default(),
+///     &[&Foo::INFO],
+///     &diff.manifest,
+/// )
+/// .unwrap();
+/// assert!(diff.stats.type_names.is_empty());
+/// assert!(diff.removed.is_empty());
+/// ```
+#[cfg(feature = "std")]
+pub fn write_changed_definitions<W>(
+    writer: W,
+    options: DefinitionFileOptions<'_>,
+    type_infos: &[&'static TypeInfo],
+    previous: &Manifest,
+) -> io::Result<ManifestDiff>
+where
+    W: io::Write,
+{
+    use crate::iter_def_deps::{hash_def_schema, hash_type_def, IterDefDeps};
+
+    let root_namespace = options.root_namespace.map(ToOwned::to_owned);
+    let mut manifest = Manifest::default();
+    // unchanged definitions are treated as if they were already emitted
+    let mut unchanged = SharedDefinitions::new();
+    let is_external = |def: &TypeDefinition| options.is_external(def);
+    for type_def in IterDefDeps::with_external(type_infos, &is_external) {
+        let name = type_def.qualified_name();
+        let hash = hash_def_schema(type_def);
+        if previous.type_hashes.get(&name) == Some(&hash) {
+            unchanged
+                .defs
+                .insert(hash_type_def(type_def), root_namespace.clone());
+        }
+        manifest.type_hashes.insert(name, hash);
+    }
+    let removed = previous
+        .type_hashes
+        .keys()
+        .filter(|name| !manifest.type_hashes.contains_key(*name))
+        .cloned()
+        .collect();
+    let mut writer = IoWriter::new(writer);
+    let result = write_definition_file_impl(
+        &mut writer,
+        DefinitionFileOptions {
+            inline_single_use: false,
+            ..options
+        },
+        type_infos,
+        &unchanged,
+    );
+    let (stats, _) = writer.into_result(result)?;
+    Ok(ManifestDiff {
+        stats,
+        manifest,
+        removed,
+    })
+}
+
+/// An error from writing a definition file.
+enum WriteError {
+    /// The underlying writer failed.
+    Fmt(fmt::Error),
+    /// A generated alias has the same qualified name as a definition.
+    NameConflict(String),
+}
+
+impl From<fmt::Error> for WriteError {
+    fn from(err: fmt::Error) -> Self {
+        Self::Fmt(err)
+    }
+}
+
+/// Writes a definition file, returning the stats and the hashes of the
+/// definitions which were inlined.
+fn write_definition_file_impl<W>(
+    writer: W,
+    options: DefinitionFileOptions<'_>,
+    type_infos: &[&'static TypeInfo],
+    shared: &SharedDefinitions,
+) -> Result<(Stats, BTreeSet<u64>), WriteError>
+where
+    W: fmt::Write,
+{
+    let mut writer = LineEndingWriter {
+        inner: writer,
+        line_ending: options.line_ending,
+    };
+    let inlined = if options.inline_single_use {
+        single_use_defs(type_infos, &options, shared)
+    } else {
+        BTreeSet::new()
+    };
+    let mut ctx = EmitCtx::new(&mut writer, options, &inlined, shared);
+    if options.utf8_bom {
+        write!(&mut ctx.w, "\u{feff}")?;
+    }
     if let Some(header) = options.header {
-        writeln!(&mut ctx.w, "{}", header)?;
+        match options.header_style {
+            HeaderStyle::Verbatim => writeln!(&mut ctx.w, "{}", header)?,
+            HeaderStyle::Line => {
+                write_comment_lines(ctx.w, "//", header)?;
+            }
+            HeaderStyle::Block => {
+                writeln!(&mut ctx.w, "/*")?;
+                write_comment_lines(ctx.w, " *", header)?;
+                writeln!(&mut ctx.w, " */")?;
+            }
+        }
+    }
+    if let Some((name, version)) = options.generated_from {
+        writeln!(&mut ctx.w, "// Generated from {} v{}", name, version)?;
+    }
+    if let Some(package_docs) = options.package_docs {
+        writeln!(&mut ctx.w, "/**")?;
+        write_comment_lines(ctx.w, " *", package_docs)?;
+        writeln!(&mut ctx.w, " *")?;
+        writeln!(&mut ctx.w, " * @packageDocumentation")?;
+        writeln!(&mut ctx.w, " */")?;
+    }
+    if let Some(declare_module) = options.declare_module {
+        if options.prettier_ignore {
+            writeln!(&mut ctx.w, "// prettier-ignore")?;
+        }
+        writeln!(&mut ctx.w, "declare module {:?} {{", declare_module)?;
+        ctx.indent();
+    }
+    let indentation = ctx.current_indentation();
+    if !options.external_imports.is_empty() {
+        let is_external = |def: &TypeDefinition| options.is_external(def);
+        let referenced = crate::iter_def_deps::IterDefDeps::with_external(
+            type_infos,
+            &is_external,
+        )
+        .map(TypeDefinition::qualified_name)
+        .collect::<BTreeSet<_>>();
+        for (name, path) in options.external_imports {
+            if referenced.contains(*name) {
+                let import_name = options.external_import_name(name);
+                let name = unqualified_name(name);
+                if import_name == name {
+                    writeln!(
+                        &mut ctx.w,
+                        "{}import type {{ {} }} from {:?};",
+                        indentation, name, path
+                    )?;
+                } else {
+                    writeln!(
+                        &mut ctx.w,
+                        "{}import type {{ {} as {} }} from {:?};",
+                        indentation, name, import_name, path
+                    )?;
+                }
+            }
+        }
     }
     if let Some(root_namespace) = options.root_namespace {
-        writeln!(&mut ctx.w, "export default {};", root_namespace)?;
-        writeln!(&mut ctx.w, "export namespace {} {{", root_namespace)?;
+        if options.module_system == ModuleSystem::CommonJs {
+            writeln!(
+                &mut ctx.w,
+                "{}export = {};",
+                indentation, root_namespace
+            )?;
+        } else {
+            writeln!(
+                &mut ctx.w,
+                "{}export default {};",
+                indentation, root_namespace
+            )?;
+        }
+        if let Some(root_namespace_docs) = options.root_namespace_docs {
+            writeln!(&mut ctx.w, "{}/**", indentation)?;
+            write_comment_lines(
+                ctx.w,
+                &format!("{} *", indentation),
+                root_namespace_docs,
+            )?;
+            writeln!(&mut ctx.w, "{} */", indentation)?;
+        }
+        // an ambient module is already ignored as a whole
+        if options.prettier_ignore && options.declare_module.is_none() {
+            writeln!(&mut ctx.w, "// prettier-ignore")?;
+        }
+        if options.module_system == ModuleSystem::CommonJs {
+            writeln!(
+                &mut ctx.w,
+                "{}namespace {} {{",
+                indentation, root_namespace
+            )?;
+        } else {
+            writeln!(
+                &mut ctx.w,
+                "{}export namespace {} {{",
+                indentation, root_namespace
+            )?;
+        }
         ctx.indent();
     }
-    ctx.emit_type_def(type_infos)?;
+    ctx.emit_type_def(type_infos).map_err(|err| {
+        ctx.name_conflict
+            .take()
+            .map_or(WriteError::Fmt(err), WriteError::NameConflict)
+    })?;
+    // an export assignment cannot be combined with other exports, so the
+    // constant is exported from the root namespace instead
+    let all_type_names_in_namespace = options.module_system
+        == ModuleSystem::CommonJs
+        && options.root_namespace.is_some();
+    if let Some(all_type_names) = options.all_type_names {
+        if all_type_names_in_namespace {
+            let indentation = ctx.current_indentation();
+            write_all_type_names(
+                ctx.w,
+                &indentation,
+                all_type_names,
+                &ctx.stats.type_names,
+            )?;
+        }
+    }
     if options.root_namespace.is_some() {
+        ctx.deindent();
+        writeln!(&mut ctx.w, "{}}}", indentation)?;
+    }
+    if let Some(all_type_names) = options.all_type_names {
+        if !all_type_names_in_namespace {
+            write_all_type_names(
+                ctx.w,
+                &indentation,
+                all_type_names,
+                &ctx.stats.type_names,
+            )?;
+        }
+    }
+    if options.declare_module.is_some() {
         ctx.deindent();
         writeln!(&mut ctx.w, "}}")?;
     }
     debug_assert_eq!(ctx.indent, 0, "indentation must be 0 after printing");
-    Ok(ctx.stats)
+    Ok((ctx.stats, inlined))
+}
+
+/// Writes the exported constant `name` listing the given type names.
+fn write_all_type_names(
+    w: &mut dyn fmt::Write,
+    indentation: &str,
+    name: &str,
+    type_names: &[String],
+) -> fmt::Result {
+    write!(w, "{}export const {} = [", indentation, name)?;
+    let mut first = true;
+    for type_name in type_names {
+        if !first {
+            write!(w, ", ")?;
+        }
+        write!(w, "{:?}", type_name)?;
+        first = false;
+    }
+    writeln!(w, "] as const;")
+}
+
+/// Returns a name for a type variable of a generated alias, which is `base`
+/// followed by as many `_` as needed to differ from the generic variables of
+/// the definition.
+fn unused_generic_var(generic_vars: &[Ident], base: &str) -> String {
+    let mut var = String::from(base);
+    while generic_vars.iter().any(|Ident(other)| *other == var) {
+        var.push('_');
+    }
+    var
+}
+
+/// Writes each line of `text` as a line of a comment starting with `prefix`
+/// (such as `//` or ` *`), which is separated from non-empty lines by a space.
+fn write_comment_lines(
+    w: &mut dyn fmt::Write,
+    prefix: &str,
+    text: &str,
+) -> fmt::Result {
+    for line in text.lines() {
+        if line.is_empty() {
+            writeln!(w, "{}", prefix)?;
+        } else {
+            writeln!(w, "{} {}", prefix, line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Finds the name of the discriminant field of a tagged union type.
+///
+/// This is the first field of every member of the union, which must always
+/// have a string literal type.
+fn union_tag(expr: &TypeExpr) -> Option<&'static str> {
+    let members = match expr {
+        TypeExpr::Union(TypeUnion { docs: _, members }) => members,
+        _ => return None,
+    };
+    let mut tag = None;
+    for member in *members {
+        let object = match member {
+            TypeExpr::Object(object) => object,
+            TypeExpr::Intersection(TypeIntersection {
+                docs: _,
+                members: [TypeExpr::Object(object), ..],
+            }) => object,
+            _ => return None,
+        };
+        match object.fields.first() {
+            Some(ObjectField {
+                docs: _,
+                name: TypeString { docs: _, value },
+                optional: false,
+                readonly: _,
+                r#type: TypeExpr::String(_),
+            }) if tag.map_or(true, |tag| tag == *value) => tag = Some(*value),
+            _ => return None,
+        }
+    }
+    tag
+}
+
+/// The maximum depth of type references followed when inspecting a type
+/// expression, which bounds the work done for deeply nested types.
+const MAX_DEPTH: usize = 32;
+
+/// Whether a type expression is an object type or an intersection of object
+/// types, including references to them.
+fn is_object_type(expr: &'static TypeExpr, depth: usize) -> bool {
+    if depth >= MAX_DEPTH {
+        return false;
+    }
+    match expr {
+        TypeExpr::Object(_) => true,
+        TypeExpr::Intersection(TypeIntersection { docs: _, members }) => {
+            members
+                .iter()
+                .all(|member| is_object_type(member, depth + 1))
+        }
+        TypeExpr::Ref(TypeInfo::Native(NativeTypeInfo { r#ref })) => {
+            is_object_type(r#ref, depth + 1)
+        }
+        TypeExpr::Ref(TypeInfo::Defined(DefinedTypeInfo { def, .. })) => {
+            is_object_type(&def.def, depth + 1)
+        }
+        // flattened `Option` fields are emitted as `Partial<T>`
+        TypeExpr::Name(TypeName {
+            path: [],
+            name: Ident("Partial"),
+            generic_args: [arg],
+        }) => is_object_type(arg, depth + 1),
+        _ => false,
+    }
+}
+
+/// Finds the value type of the map and the fields of the object of an
+/// intersection of a map type with `string` keys and an object type, such as
+/// for a struct with a flattened map field.
+fn flattened_map(
+    members: &'static [TypeExpr],
+) -> Option<(&'static TypeExpr, &'static [ObjectField])> {
+    fn map_value(expr: &'static TypeExpr) -> Option<&'static TypeExpr> {
+        match expr {
+            TypeExpr::Ref(TypeInfo::Native(NativeTypeInfo { r#ref })) => {
+                map_value(r#ref)
+            }
+            TypeExpr::Name(TypeName {
+                path: [],
+                name: Ident("Record"),
+                generic_args: [key, value],
+            }) if primitive_key(key, 0) == Some("string") => Some(value),
+            _ => None,
+        }
+    }
+
+    match members {
+        [map, TypeExpr::Object(TypeObject {
+            docs: None,
+            index_signature: None,
+            fields,
+        })] if !fields.is_empty() => Some((map_value(map)?, fields)),
+        _ => None,
+    }
+}
+
+/// Finds the primitive type (`string` or `number`) of a map key type, if it
+/// has one, for emitting maps with [`MapStyle::IndexSignature`].
+fn primitive_key(
+    expr: &'static TypeExpr,
+    depth: usize,
+) -> Option<&'static str> {
+    if depth >= MAX_DEPTH {
+        return None;
+    }
+    match expr {
+        TypeExpr::Ref(TypeInfo::Native(NativeTypeInfo { r#ref })) => {
+            primitive_key(r#ref, depth + 1)
+        }
+        TypeExpr::Ref(TypeInfo::Defined(DefinedTypeInfo { def, .. })) => {
+            primitive_key(&def.def, depth + 1)
+        }
+        TypeExpr::Name(TypeName {
+            path: [],
+            name: Ident(name @ ("string" | "number")),
+            generic_args: [],
+        }) => Some(name),
+        _ => None,
+    }
+}
+
+/// Finds the values of the discriminant field of a tagged union type (as found
+/// by [`union_tag`]), in the order of the union's members.
+fn union_tag_values(expr: &TypeExpr) -> Option<Vec<TypeString>> {
+    union_tag(expr)?;
+    let members = match expr {
+        TypeExpr::Union(TypeUnion { docs: _, members }) => members,
+        _ => return None,
+    };
+    let mut values: Vec<TypeString> = Vec::new();
+    for member in *members {
+        let object = match member {
+            TypeExpr::Object(object) => object,
+            TypeExpr::Intersection(TypeIntersection {
+                docs: _,
+                members: [TypeExpr::Object(object), ..],
+            }) => object,
+            _ => return None,
+        };
+        if let Some(ObjectField {
+            r#type: TypeExpr::String(TypeString { docs: _, value }),
+            ..
+        }) = object.fields.first()
+        {
+            if !values.iter().any(|other| other.value == *value) {
+                values.push(TypeString { docs: None, value });
+            }
+        }
+    }
+    Some(values)
+}
+
+/// The docs emitted for floats with [`DefinitionFileOptions::finite_floats`].
+const FINITE_DOCS: Docs = Docs(
+    "JSON cannot represent `NaN` or infinite values, so this is always a\n\
+     finite number.",
+);
+
+/// The branded type emitted for floats with
+/// [`DefinitionFileOptions::finite_floats`].
+const FINITE_NUMBER: TypeExpr = TypeExpr::Intersection(TypeIntersection {
+    docs: None,
+    members: &[
+        TypeExpr::ident(Ident("number")),
+        TypeExpr::Object(TypeObject {
+            docs: None,
+            index_signature: None,
+            fields: &[ObjectField {
+                docs: None,
+                name: TypeString {
+                    docs: None,
+                    value: "__brand",
+                },
+                optional: false,
+                readonly: true,
+                r#type: TypeExpr::String(TypeString {
+                    docs: None,
+                    value: "Finite",
+                }),
+            }],
+        }),
+    ],
+});
+
+/// Returns whether a type definition is the one for `f32` or `f64`.
+fn is_float_def(def: &TypeDefinition) -> bool {
+    use crate::iter_def_deps::hash_type_def;
+
+    let hash = hash_type_def(def);
+    [<f32 as TypeDef>::INFO, <f64 as TypeDef>::INFO]
+        .iter()
+        .any(|info| match info {
+            TypeInfo::Defined(DefinedTypeInfo { def, .. }) => {
+                hash_type_def(def) == hash
+            }
+            TypeInfo::Native(_) => false,
+        })
+}
+
+/// Finds the type definitions which can be inlined because they are referenced
+/// exactly once, returning their hashes.
+fn single_use_defs(
+    type_infos: &[&'static TypeInfo],
+    options: &DefinitionFileOptions<'_>,
+    shared: &SharedDefinitions,
+) -> BTreeSet<u64> {
+    use crate::iter_def_deps::{count_def_refs, hash_type_def, IterDefDeps};
+
+    // the bodies of external definitions are not emitted, so their
+    // references are not counted
+    let is_external = |def: &TypeDefinition| options.is_external(def);
+    let defs = IterDefDeps::with_external(type_infos, &is_external)
+        .filter(|def| !is_external(def))
+        .collect::<Vec<_>>();
+    let ref_counts = count_def_refs(&defs);
+    let roots = type_infos
+        .iter()
+        .filter_map(|info| match info {
+            TypeInfo::Native(_) => None,
+            TypeInfo::Defined(DefinedTypeInfo { def, .. }) => {
+                Some(hash_type_def(def))
+            }
+        })
+        .collect::<BTreeSet<_>>();
+    defs.into_iter()
+        // generic definitions can't be inlined without substituting their
+        // generic arguments
+        .filter(|def| def.generic_vars.is_empty())
+        .map(hash_type_def)
+        .filter(|hash| {
+            ref_counts.get(hash) == Some(&1)
+                && !roots.contains(hash)
+                && !shared.defs.contains_key(hash)
+        })
+        .collect()
 }
 
 impl TypeInfo {
@@ -666,17 +2645,25 @@ impl TypeInfo {
     /// "#
     /// );
     /// ```
+    #[cfg(feature = "std")]
     pub fn write_ref_expr<W>(
         &'static self,
-        mut writer: W,
+        writer: W,
         root_namespace: Option<&str>,
     ) -> io::Result<()>
     where
         W: io::Write,
     {
-        let mut ctx = EmitCtx::new(&mut writer, root_namespace);
-        ctx.emit_type_ref(self)?;
+        let options = DefinitionFileOptions {
+            root_namespace,
+            ..Default::default()
+        };
+        let inlined = BTreeSet::new();
+        let shared = SharedDefinitions::new();
+        let mut writer = IoWriter::new(writer);
+        let mut ctx = EmitCtx::new(&mut writer, options, &inlined, &shared);
+        let result = ctx.emit_type_ref(self);
         debug_assert_eq!(ctx.indent, 0, "indentation must be 0 after printing");
-        Ok(())
+        writer.into_result(result.map_err(WriteError::Fmt))
     }
 }
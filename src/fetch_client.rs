@@ -0,0 +1,175 @@
+//! This module defines typed [`fetch`] client functions for HTTP routes.
+//!
+//! [`fetch`]: https://developer.mozilla.org/en-US/docs/Web/API/fetch
+//!
+//! Each `:param` segment of a route's path must name a field of the request,
+//! either by its Rust name or by its serialized name:
+//! ```compile_fail
+//! use serde::Serialize;
+//! use typescript_type_def::TypeDef;
+//!
+//! #[derive(Serialize, TypeDef)]
+//! #[type_def(route = "PUT /users/:id")]
+//! struct RenameUser {
+//!     user_id: usize,
+//!     name: String,
+//! }
+//! ```
+//!
+//! Since `GET` and `DELETE` requests have no body, every field of their
+//! requests must be a path parameter:
+//! ```compile_fail
+//! use serde::Serialize;
+//! use typescript_type_def::TypeDef;
+//!
+//! #[derive(Serialize, TypeDef)]
+//! #[type_def(route = "GET /users/:id")]
+//! struct GetUser {
+//!     id: usize,
+//!     verbose: bool,
+//! }
+//! ```
+
+use crate::{type_expr::TypeInfo, TypeDef};
+use std::io;
+
+/// A Rust type which is the request of an HTTP route.
+///
+/// This trait is implemented by
+/// [`#[derive(TypeDef)]`](macro@crate::TypeDef) for types with a
+/// `#[type_def(route = "METHOD /path")]` attribute.
+pub trait Route: TypeDef {
+    /// A constant value describing the route.
+    const ROUTE: RouteInfo;
+}
+
+/// A description of an HTTP route.
+#[derive(Debug, Clone, Copy)]
+pub struct RouteInfo {
+    /// The name of the client function for this route.
+    pub name: &'static str,
+    /// The HTTP method of this route, such as `GET`.
+    pub method: &'static str,
+    /// The path of this route.
+    ///
+    /// Segments of the form `:name` are replaced with the field of the
+    /// request which is serialized as `name`.
+    pub path: &'static str,
+    /// The type of the request of this route.
+    pub request: &'static TypeInfo,
+    /// The type of the JSON response of this route, if it has one.
+    pub response: Option<&'static TypeInfo>,
+}
+
+/// Writes a TypeScript `fetch` client function for each of the given routes
+/// to the given writer.
+///
+/// Requests of `GET` and `DELETE` routes are only used to fill the route's
+/// path (so all of their fields must be path parameters), while the requests
+/// of other routes are also sent as the JSON body. Type references use the
+/// given root namespace, so the client should import a definition file
+/// written with the same
+/// [`root_namespace`](crate::DefinitionFileOptions::root_namespace).
+///
+/// # Example
+/// ```
+/// use serde::Serialize;
+/// use typescript_type_def::{
+///     fetch_client::{write_fetch_client, Route},
+///     TypeDef,
+/// };
+///
+/// #[derive(Serialize, TypeDef)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[derive(Serialize, TypeDef)]
+/// #[type_def(route = "GET /users/:id", response = "User")]
+/// struct GetUser {
+///     id: String,
+/// }
+///
+/// let mut buf = Vec::new();
+/// write_fetch_client(&mut buf, Some("types"), &[GetUser::ROUTE]).unwrap();
+/// assert_eq!(
+///     String::from_utf8(buf).unwrap(),
+///     r#"export async function getUser(request: types.GetUser): Promise<types.User> {
+///     const response = await fetch(`/users/${encodeURIComponent(String(request["id"]))}`, {
+///         method: "GET",
+///     });
+///     if (!response.ok) {
+///         throw new Error(response.statusText);
+///     }
+///     return await response.json();
+/// }
+/// "#
+/// );
+/// ```
+pub fn write_fetch_client<W>(
+    mut writer: W,
+    root_namespace: Option<&str>,
+    routes: &[RouteInfo],
+) -> io::Result<()>
+where
+    W: io::Write,
+{
+    for RouteInfo {
+        name,
+        method,
+        path,
+        request,
+        response,
+    } in routes
+    {
+        write!(writer, "export async function {}(request: ", name)?;
+        request.write_ref_expr(&mut writer, root_namespace)?;
+        write!(writer, "): Promise<")?;
+        if let Some(response) = response {
+            response.write_ref_expr(&mut writer, root_namespace)?;
+        } else {
+            write!(writer, "void")?;
+        }
+        writeln!(writer, "> {{")?;
+        write!(writer, "    const response = await fetch(`")?;
+        let mut first = true;
+        for segment in path.split('/') {
+            if !first {
+                write!(writer, "/")?;
+            }
+            if let Some(param) = segment.strip_prefix(':') {
+                write!(
+                    writer,
+                    "${{encodeURIComponent(String(request[{:?}]))}}",
+                    param
+                )?;
+            } else {
+                for c in segment.chars() {
+                    if matches!(c, '`' | '$' | '\\') {
+                        write!(writer, "\\")?;
+                    }
+                    write!(writer, "{}", c)?;
+                }
+            }
+            first = false;
+        }
+        writeln!(writer, "`, {{")?;
+        writeln!(writer, "        method: {:?},", method)?;
+        if !matches!(*method, "GET" | "DELETE") {
+            writeln!(
+                writer,
+                "        headers: {{ \"Content-Type\": \"application/json\" }},"
+            )?;
+            writeln!(writer, "        body: JSON.stringify(request),")?;
+        }
+        writeln!(writer, "    }});")?;
+        writeln!(writer, "    if (!response.ok) {{")?;
+        writeln!(writer, "        throw new Error(response.statusText);")?;
+        writeln!(writer, "    }}")?;
+        if response.is_some() {
+            writeln!(writer, "    return await response.json();")?;
+        }
+        writeln!(writer, "}}")?;
+    }
+    Ok(())
+}
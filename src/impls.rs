@@ -6,6 +6,7 @@ use crate::{
         TypeTuple, TypeUnion,
     },
 };
+use alloc::{borrow::ToOwned, boxed::Box, string::String, vec::Vec};
 
 macro_rules! impl_native {
     ($ty:ty, $ts_ty:literal) => {
@@ -27,16 +28,25 @@ impl_native!(bool, "boolean");
 impl_native!(String, "string");
 impl_native!(str, "string");
 impl_native!(char, "string");
+#[cfg(feature = "std")]
 impl_native!(std::path::PathBuf, "string");
+#[cfg(feature = "std")]
 impl_native!(std::path::Path, "string");
+#[cfg(feature = "std")]
 impl_native!(std::ffi::CString, "string");
+#[cfg(feature = "std")]
 impl_native!(std::ffi::CStr, "string");
+#[cfg(feature = "std")]
 impl_native!(std::ffi::OsString, "string");
+#[cfg(feature = "std")]
 impl_native!(std::ffi::OsStr, "string");
 #[cfg(feature = "json_value")]
 impl_native!(serde_json::Number, "number");
+#[cfg(feature = "std")]
 impl_native!(std::net::IpAddr, "string");
+#[cfg(feature = "std")]
 impl_native!(std::net::Ipv4Addr, "string");
+#[cfg(feature = "std")]
 impl_native!(std::net::Ipv6Addr, "string");
 
 macro_rules! impl_number {
@@ -48,7 +58,10 @@ macro_rules! impl_number {
                     path: &[],
                     name: Ident(stringify!($name)),
                     generic_vars: &[],
+                    generic_defaults: &[],
                     def: TypeExpr::ident(Ident("number")),
+                    emit_schema: false,
+                    interface: None,
                 },
                 generic_args: &[],
             });
@@ -66,16 +79,16 @@ impl_number!(i16, I16);
 impl_number!(i32, I32);
 impl_number!(i64, I64);
 impl_number!(isize, Isize);
-impl_number!(std::num::NonZeroU8, NonZeroU8);
-impl_number!(std::num::NonZeroU16, NonZeroU16);
-impl_number!(std::num::NonZeroU32, NonZeroU32);
-impl_number!(std::num::NonZeroU64, NonZeroU64);
-impl_number!(std::num::NonZeroUsize, NonZeroUsize);
-impl_number!(std::num::NonZeroI8, NonZeroI8);
-impl_number!(std::num::NonZeroI16, NonZeroI16);
-impl_number!(std::num::NonZeroI32, NonZeroI32);
-impl_number!(std::num::NonZeroI64, NonZeroI64);
-impl_number!(std::num::NonZeroIsize, NonZeroIsize);
+impl_number!(core::num::NonZeroU8, NonZeroU8);
+impl_number!(core::num::NonZeroU16, NonZeroU16);
+impl_number!(core::num::NonZeroU32, NonZeroU32);
+impl_number!(core::num::NonZeroU64, NonZeroU64);
+impl_number!(core::num::NonZeroUsize, NonZeroUsize);
+impl_number!(core::num::NonZeroI8, NonZeroI8);
+impl_number!(core::num::NonZeroI16, NonZeroI16);
+impl_number!(core::num::NonZeroI32, NonZeroI32);
+impl_number!(core::num::NonZeroI64, NonZeroI64);
+impl_number!(core::num::NonZeroIsize, NonZeroIsize);
 impl_number!(f32, F32);
 impl_number!(f64, F64);
 
@@ -169,6 +182,62 @@ where
     const INFO: TypeInfo = list_type_info!(T);
 }
 
+#[cfg(feature = "arrayvec")]
+impl<T, const CAP: usize> TypeDef for arrayvec::ArrayVec<T, CAP>
+where
+    T: TypeDef,
+{
+    const INFO: TypeInfo = list_type_info!(T);
+}
+
+#[cfg(feature = "arrayvec")]
+impl<const CAP: usize> TypeDef for arrayvec::ArrayString<CAP> {
+    const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
+        r#ref: TypeExpr::ident(Ident("string")),
+    });
+}
+
+#[cfg(feature = "smallvec")]
+impl<A> TypeDef for smallvec::SmallVec<A>
+where
+    A: smallvec::Array + 'static,
+    A::Item: TypeDef,
+{
+    const INFO: TypeInfo = list_type_info!(A::Item);
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz> TypeDef for chrono::DateTime<Tz>
+where
+    Tz: chrono::TimeZone + 'static,
+{
+    const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
+        r#ref: TypeExpr::ident(Ident("string")),
+    });
+}
+
+#[cfg(feature = "chrono")]
+impl_native!(chrono::NaiveDate, "string");
+#[cfg(feature = "chrono")]
+impl_native!(chrono::NaiveTime, "string");
+#[cfg(feature = "chrono")]
+impl_native!(chrono::NaiveDateTime, "string");
+#[cfg(feature = "chrono")]
+impl_native!(chrono::Weekday, "string");
+#[cfg(feature = "chrono")]
+impl_native!(chrono::Month, "string");
+
+#[cfg(feature = "jiff")]
+impl_native!(jiff::Timestamp, "string");
+#[cfg(feature = "jiff")]
+impl_native!(jiff::civil::Date, "string");
+#[cfg(feature = "jiff")]
+impl_native!(jiff::civil::Time, "string");
+#[cfg(feature = "jiff")]
+impl_native!(jiff::civil::DateTime, "string");
+#[cfg(feature = "jiff")]
+impl_native!(jiff::Span, "string");
+
 macro_rules! set_type_info {
     ($item:ty) => {
         TypeInfo::Native(NativeTypeInfo {
@@ -180,6 +249,7 @@ macro_rules! set_type_info {
     };
 }
 
+#[cfg(feature = "std")]
 impl<T, S> TypeDef for std::collections::HashSet<T, S>
 where
     T: TypeDef,
@@ -188,7 +258,7 @@ where
     const INFO: TypeInfo = set_type_info!(T);
 }
 
-impl<T> TypeDef for std::collections::BTreeSet<T>
+impl<T> TypeDef for alloc::collections::BTreeSet<T>
 where
     T: TypeDef,
 {
@@ -210,6 +280,7 @@ macro_rules! map_type_info {
     };
 }
 
+#[cfg(feature = "std")]
 impl<K, V, S> TypeDef for std::collections::HashMap<K, V, S>
 where
     K: TypeDef,
@@ -219,7 +290,7 @@ where
     const INFO: TypeInfo = map_type_info!(K, V);
 }
 
-impl<K, V> TypeDef for std::collections::BTreeMap<K, V>
+impl<K, V> TypeDef for alloc::collections::BTreeMap<K, V>
 where
     K: TypeDef,
     V: TypeDef,
@@ -254,16 +325,16 @@ where
     });
 }
 
-impl<T> TypeDef for std::borrow::Cow<'static, T>
+impl<T> TypeDef for alloc::borrow::Cow<'static, T>
 where
-    T: Clone + TypeDef + ?Sized,
+    T: ToOwned + TypeDef + ?Sized,
 {
     const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
         r#ref: TypeExpr::Ref(&T::INFO),
     });
 }
 
-impl<T> TypeDef for std::marker::PhantomData<T>
+impl<T> TypeDef for core::marker::PhantomData<T>
 where
     T: TypeDef + ?Sized,
 {
@@ -272,7 +343,22 @@ where
     });
 }
 
-impl<T, E> TypeDef for std::result::Result<T, E>
+impl TypeDef for core::marker::PhantomPinned {
+    const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
+        r#ref: TypeExpr::ident(Ident("null")),
+    });
+}
+
+impl<T> TypeDef for core::cmp::Reverse<T>
+where
+    T: TypeDef,
+{
+    const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
+        r#ref: TypeExpr::Ref(&T::INFO),
+    });
+}
+
+impl<T, E> TypeDef for core::result::Result<T, E>
 where
     T: TypeDef,
     E: TypeDef,
@@ -291,6 +377,7 @@ where
                             value: "Ok",
                         },
                         optional: false,
+                        readonly: false,
                         r#type: TypeExpr::Ref(&T::INFO),
                     }],
                 }),
@@ -304,6 +391,7 @@ where
                             value: "Err",
                         },
                         optional: false,
+                        readonly: false,
                         r#type: TypeExpr::Ref(&E::INFO),
                     }],
                 }),
@@ -312,6 +400,127 @@ where
     });
 }
 
+impl<T> TypeDef for core::ops::Bound<T>
+where
+    T: TypeDef,
+{
+    const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
+        r#ref: TypeExpr::Union(TypeUnion {
+            docs: None,
+            members: &[
+                TypeExpr::Object(TypeObject {
+                    docs: None,
+                    index_signature: None,
+                    fields: &[ObjectField {
+                        docs: None,
+                        name: TypeString {
+                            docs: None,
+                            value: "Included",
+                        },
+                        optional: false,
+                        readonly: false,
+                        r#type: TypeExpr::Ref(&T::INFO),
+                    }],
+                }),
+                TypeExpr::Object(TypeObject {
+                    docs: None,
+                    index_signature: None,
+                    fields: &[ObjectField {
+                        docs: None,
+                        name: TypeString {
+                            docs: None,
+                            value: "Excluded",
+                        },
+                        optional: false,
+                        readonly: false,
+                        r#type: TypeExpr::Ref(&T::INFO),
+                    }],
+                }),
+                TypeExpr::String(TypeString {
+                    docs: None,
+                    value: "Unbounded",
+                }),
+            ],
+        }),
+    });
+}
+
+macro_rules! impl_range {
+    ($ty:ident, $($field:literal),+) => {
+        impl<T> TypeDef for core::ops::$ty<T>
+        where
+            T: TypeDef,
+        {
+            const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
+                r#ref: TypeExpr::Object(TypeObject {
+                    docs: None,
+                    index_signature: None,
+                    fields: &[$(
+                        ObjectField {
+                            docs: None,
+                            name: TypeString {
+                                docs: None,
+                                value: $field,
+                            },
+                            optional: false,
+                            readonly: false,
+                            r#type: TypeExpr::Ref(&T::INFO),
+                        },
+                    )+],
+                }),
+            });
+        }
+    };
+}
+
+impl_range!(Range, "start", "end");
+impl_range!(RangeInclusive, "start", "end");
+impl_range!(RangeFrom, "start");
+impl_range!(RangeTo, "end");
+
+macro_rules! impl_time {
+    ($ty:ty, $secs:literal, $nanos:literal) => {
+        impl TypeDef for $ty {
+            const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
+                r#ref: TypeExpr::Object(TypeObject {
+                    docs: None,
+                    index_signature: None,
+                    fields: &[
+                        ObjectField {
+                            docs: None,
+                            name: TypeString {
+                                docs: None,
+                                value: $secs,
+                            },
+                            optional: false,
+                            readonly: false,
+                            r#type: TypeExpr::Ref(&u64::INFO),
+                        },
+                        ObjectField {
+                            docs: None,
+                            name: TypeString {
+                                docs: None,
+                                value: $nanos,
+                            },
+                            optional: false,
+                            readonly: false,
+                            r#type: TypeExpr::Ref(&u32::INFO),
+                        },
+                    ],
+                }),
+            });
+        }
+    };
+}
+
+impl_time!(core::time::Duration, "secs", "nanos");
+#[cfg(feature = "std")]
+impl_time!(
+    std::time::SystemTime,
+    "secs_since_epoch",
+    "nanos_since_epoch"
+);
+
 #[cfg(feature = "json_value")]
 impl TypeDef for serde_json::Value {
     const INFO: TypeInfo = TypeInfo::Defined(DefinedTypeInfo {
@@ -320,6 +529,7 @@ impl TypeDef for serde_json::Value {
             path: &[],
             name: Ident("JSONValue"),
             generic_vars: &[],
+            generic_defaults: &[],
             def: TypeExpr::Union(TypeUnion {
                 docs: None,
                 members: &[
@@ -344,6 +554,8 @@ impl TypeDef for serde_json::Value {
                     }),
                 ],
             }),
+            emit_schema: false,
+            interface: None,
         },
         generic_args: &[],
     });
@@ -1,11 +1,14 @@
 use crate::type_expr::{
-    DefinedTypeInfo, Ident, IndexSignature, NativeTypeInfo, ObjectField,
+    DefinedTypeInfo, Docs, Ident, IndexSignature, NativeTypeInfo, ObjectField,
     TypeArray, TypeDefinition, TypeExpr, TypeInfo, TypeIntersection, TypeName,
     TypeObject, TypeString, TypeTuple, TypeUnion,
 };
-use std::{
-    collections::HashSet,
-    hash::Hash,
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{
+    hash::{Hash, Hasher},
     iter::{self, FusedIterator},
     slice,
 };
@@ -14,25 +17,45 @@ use std::{
 ///
 /// Type definitions dependencies (including those of generic types) are
 /// produced exactly once in post-order.
-pub struct IterDefDeps {
+pub struct IterDefDeps<'a> {
     stack: Vec<TypeExpr>,
-    visited: HashSet<u64>,
-    emitted: HashSet<u64>,
+    visited: BTreeSet<u64>,
+    emitted: BTreeSet<u64>,
+    is_external: &'a dyn Fn(&TypeDefinition) -> bool,
 }
 
-impl IterDefDeps {
+impl IterDefDeps<'static> {
     /// Creates a new iterator of the dependencies of the given type info.
     pub fn new(roots: &[&'static TypeInfo]) -> Self {
+        fn never(_: &TypeDefinition) -> bool {
+            false
+        }
+
+        Self::with_external(roots, &never)
+    }
+}
+
+impl<'a> IterDefDeps<'a> {
+    /// Creates a new iterator of the dependencies of the given type info,
+    /// which doesn't produce the dependencies of external definitions.
+    ///
+    /// External definitions themselves are still produced, but only the
+    /// generic arguments they are used with are walked, not their bodies.
+    pub fn with_external(
+        roots: &[&'static TypeInfo],
+        is_external: &'a dyn Fn(&TypeDefinition) -> bool,
+    ) -> Self {
         Self {
             // reverse order so they are popped from the stack in original order
             stack: roots.iter().rev().map(|x| TypeExpr::Ref(x)).collect(),
-            visited: HashSet::new(),
-            emitted: HashSet::new(),
+            visited: BTreeSet::new(),
+            emitted: BTreeSet::new(),
+            is_external,
         }
     }
 }
 
-impl Iterator for IterDefDeps {
+impl Iterator for IterDefDeps<'_> {
     type Item = &'static TypeDefinition;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -40,9 +63,10 @@ impl Iterator for IterDefDeps {
             stack,
             visited,
             emitted,
+            is_external,
         } = self;
         while let Some(expr) = stack.pop() {
-            if TypeExprChildren::new(&expr).all(|child| {
+            if TypeExprChildren::walked(&expr, *is_external).all(|child| {
                 visited.contains(&hash_type_expr(child, HashKind::Visit))
             }) {
                 let expr_visit_hash = hash_type_expr(&expr, HashKind::Visit);
@@ -61,7 +85,7 @@ impl Iterator for IterDefDeps {
             } else {
                 stack.push(expr);
                 stack.extend(
-                    TypeExprChildren::new(&expr)
+                    TypeExprChildren::walked(&expr, *is_external)
                         .filter(|expr| {
                             !visited.contains(&hash_type_expr(
                                 expr,
@@ -76,7 +100,43 @@ impl Iterator for IterDefDeps {
     }
 }
 
-impl FusedIterator for IterDefDeps {}
+impl FusedIterator for IterDefDeps<'_> {}
+
+/// Counts the references to each type definition from the bodies of the given
+/// type definitions.
+///
+/// The counts are keyed by [`hash_type_def`].
+pub(crate) fn count_def_refs(
+    defs: &[&'static TypeDefinition],
+) -> BTreeMap<u64, usize> {
+    let mut counts = BTreeMap::new();
+    let mut stack = defs
+        .iter()
+        .flat_map(
+            |TypeDefinition {
+                 def,
+                 generic_defaults,
+                 ..
+             }| {
+                iter::once(def).chain(generic_defaults.iter().flatten())
+            },
+        )
+        .collect::<Vec<_>>();
+    while let Some(expr) = stack.pop() {
+        if let TypeExpr::Ref(TypeInfo::Defined(DefinedTypeInfo {
+            def,
+            generic_args,
+        })) = expr
+        {
+            *counts.entry(hash_type_def(def)).or_default() += 1;
+            // the body of the referenced definition is counted on its own
+            stack.extend(generic_args.iter());
+        } else {
+            stack.extend(TypeExprChildren::new(expr));
+        }
+    }
+    counts
+}
 
 /// An iterator which produces all of the direct type expression children of a
 /// type expression.
@@ -84,13 +144,30 @@ enum TypeExprChildren<'a> {
     None,
     One(iter::Once<&'a TypeExpr>),
     Slice(slice::Iter<'a, TypeExpr>),
-    OneThenSlice(
-        iter::Chain<iter::Once<&'a TypeExpr>, slice::Iter<'a, TypeExpr>>,
+    Defined(
+        Option<&'a TypeExpr>,
+        slice::Iter<'a, Option<TypeExpr>>,
+        slice::Iter<'a, TypeExpr>,
     ),
     Object(Option<&'a IndexSignature>, slice::Iter<'a, ObjectField>),
 }
 
 impl<'a> TypeExprChildren<'a> {
+    /// Returns the children of a type expression which are walked by
+    /// [`IterDefDeps`], skipping the bodies of external definitions.
+    fn walked(
+        expr: &'a TypeExpr,
+        is_external: &dyn Fn(&TypeDefinition) -> bool,
+    ) -> Self {
+        match expr {
+            TypeExpr::Ref(TypeInfo::Defined(DefinedTypeInfo {
+                def,
+                generic_args,
+            })) if is_external(def) => Self::Slice(generic_args.iter()),
+            expr => Self::new(expr),
+        }
+    }
+
     fn new(expr: &'a TypeExpr) -> Self {
         match expr {
             TypeExpr::Ref(TypeInfo::Native(NativeTypeInfo { r#ref })) => {
@@ -103,12 +180,17 @@ impl<'a> TypeExprChildren<'a> {
                         path: _,
                         name: _,
                         generic_vars: _,
+                        generic_defaults,
                         def,
+                        emit_schema: _,
+                        interface: _,
                     },
                 generic_args,
-            })) => {
-                Self::OneThenSlice(iter::once(def).chain(generic_args.iter()))
-            }
+            })) => Self::Defined(
+                Some(def),
+                generic_defaults.iter(),
+                generic_args.iter(),
+            ),
             TypeExpr::Name(TypeName {
                 path: _,
                 name: _,
@@ -144,7 +226,10 @@ impl<'a> Iterator for TypeExprChildren<'a> {
             Self::None => None,
             Self::One(iter) => iter.next(),
             Self::Slice(iter) => iter.next(),
-            Self::OneThenSlice(iter) => iter.next(),
+            Self::Defined(def, generic_defaults, generic_args) => def
+                .take()
+                .or_else(|| generic_defaults.find_map(Option::as_ref))
+                .or_else(|| generic_args.next()),
             Self::Object(index_signature, iter) => index_signature
                 .take()
                 .map(
@@ -160,6 +245,7 @@ impl<'a> Iterator for TypeExprChildren<'a> {
                              docs: _,
                              name: _,
                              optional: _,
+                             readonly: _,
                              r#type,
                          }| r#type,
                     )
@@ -172,7 +258,16 @@ impl<'a> Iterator for TypeExprChildren<'a> {
             Self::None => (0, Some(0)),
             Self::One(iter) => iter.size_hint(),
             Self::Slice(iter) => iter.size_hint(),
-            Self::OneThenSlice(iter) => iter.size_hint(),
+            Self::Defined(def, generic_defaults, generic_args) => {
+                let len = usize::from(def.is_some())
+                    + generic_defaults
+                        .as_slice()
+                        .iter()
+                        .filter(|default| default.is_some())
+                        .count()
+                    + generic_args.len();
+                (len, Some(len))
+            }
             Self::Object(index_signature, iter) => {
                 let (min, max) = iter.size_hint();
                 if index_signature.is_some() {
@@ -198,7 +293,10 @@ impl DoubleEndedIterator for TypeExprChildren<'_> {
             Self::None => None,
             Self::One(iter) => iter.next_back(),
             Self::Slice(iter) => iter.next_back(),
-            Self::OneThenSlice(iter) => iter.next_back(),
+            Self::Defined(def, generic_defaults, generic_args) => generic_args
+                .next_back()
+                .or_else(|| generic_defaults.rev().find_map(Option::as_ref))
+                .or_else(|| def.take()),
             Self::Object(index_signature, iter) => iter
                 .next_back()
                 .map(
@@ -206,6 +304,7 @@ impl DoubleEndedIterator for TypeExprChildren<'_> {
                          docs: _,
                          name: _,
                          optional: _,
+                         readonly: _,
                          r#type,
                      }| { r#type },
                 )
@@ -226,17 +325,31 @@ impl DoubleEndedIterator for TypeExprChildren<'_> {
 enum HashKind {
     Visit,
     Emit,
+    /// Hashes everything which affects the emitted code, including docs, but
+    /// only the names of referenced definitions.
+    Schema,
 }
 
 fn hash_type_expr(expr: &TypeExpr, hash_kind: HashKind) -> u64 {
-    use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+    fn visit_docs(
+        docs: &Option<Docs>,
+        hash_kind: HashKind,
+        state: &mut Fnv1aHasher,
+    ) {
+        if let HashKind::Schema = hash_kind {
+            hash_docs(docs, state);
+        }
+    }
 
     fn visit_expr(
         expr: &TypeExpr,
         hash_kind: HashKind,
-        state: &mut DefaultHasher,
+        state: &mut Fnv1aHasher,
     ) {
+        // the kind of each expression and the length of each list are hashed
+        // so that different shapes with the same contents hash differently
         match expr {
+            // native references are emitted as the expression they refer to
             TypeExpr::Ref(TypeInfo::Native(NativeTypeInfo { r#ref })) => {
                 visit_expr(r#ref, hash_kind, state);
             }
@@ -247,20 +360,37 @@ fn hash_type_expr(expr: &TypeExpr, hash_kind: HashKind) -> u64 {
                         path,
                         name: Ident(name),
                         generic_vars,
+                        generic_defaults,
                         def,
+                        emit_schema: _,
+                        interface: _,
                     },
                 generic_args,
             })) => {
+                state.write_u8(0);
+                hash_len(path.len(), state);
                 for Ident(path_part) in *path {
                     path_part.hash(state);
                 }
                 name.hash(state);
-                for Ident(generic_var) in *generic_vars {
-                    generic_var.hash(state);
+                // the definitions themselves are hashed separately for schemas
+                if let HashKind::Visit | HashKind::Emit = hash_kind {
+                    hash_len(generic_vars.len(), state);
+                    for Ident(generic_var) in *generic_vars {
+                        generic_var.hash(state);
+                    }
+                    hash_len(generic_defaults.len(), state);
+                    for generic_default in *generic_defaults {
+                        generic_default.is_some().hash(state);
+                        if let Some(generic_default) = generic_default {
+                            visit_expr(generic_default, hash_kind, state);
+                        }
+                    }
+                    visit_expr(def, hash_kind, state);
                 }
-                visit_expr(def, hash_kind, state);
                 match hash_kind {
-                    HashKind::Visit => {
+                    HashKind::Visit | HashKind::Schema => {
+                        hash_len(generic_args.len(), state);
                         for generic_arg in *generic_args {
                             visit_expr(generic_arg, hash_kind, state);
                         }
@@ -273,61 +403,85 @@ fn hash_type_expr(expr: &TypeExpr, hash_kind: HashKind) -> u64 {
                 name: Ident(name),
                 generic_args,
             }) => {
+                state.write_u8(1);
+                hash_len(path.len(), state);
                 for Ident(path_part) in *path {
                     path_part.hash(state);
                 }
                 name.hash(state);
+                hash_len(generic_args.len(), state);
                 for generic_arg in *generic_args {
                     visit_expr(generic_arg, hash_kind, state);
                 }
             }
-            TypeExpr::String(TypeString { docs: _, value }) => {
+            TypeExpr::String(TypeString { docs, value }) => {
+                state.write_u8(2);
+                visit_docs(docs, hash_kind, state);
                 value.hash(state);
             }
-            TypeExpr::Tuple(TypeTuple { docs: _, elements }) => {
+            TypeExpr::Tuple(TypeTuple { docs, elements }) => {
+                state.write_u8(3);
+                visit_docs(docs, hash_kind, state);
+                hash_len(elements.len(), state);
                 for element in *elements {
                     visit_expr(element, hash_kind, state);
                 }
             }
             TypeExpr::Object(TypeObject {
-                docs: _,
+                docs,
                 index_signature,
                 fields,
             }) => {
+                state.write_u8(4);
+                visit_docs(docs, hash_kind, state);
+                index_signature.is_some().hash(state);
                 if let Some(IndexSignature {
-                    docs: _,
+                    docs,
                     name: Ident(name),
                     value,
                 }) = index_signature
                 {
+                    visit_docs(docs, hash_kind, state);
                     name.hash(state);
                     visit_expr(value, hash_kind, state);
                 }
+                hash_len(fields.len(), state);
                 for ObjectField {
-                    docs: _,
+                    docs,
                     name:
                         TypeString {
                             docs: _,
                             value: name,
                         },
                     optional,
+                    readonly,
                     r#type,
                 } in *fields
                 {
+                    visit_docs(docs, hash_kind, state);
                     name.hash(state);
                     optional.hash(state);
+                    readonly.hash(state);
                     visit_expr(r#type, hash_kind, state);
                 }
             }
-            TypeExpr::Array(TypeArray { docs: _, item }) => {
+            TypeExpr::Array(TypeArray { docs, item }) => {
+                state.write_u8(5);
+                visit_docs(docs, hash_kind, state);
                 visit_expr(item, hash_kind, state);
             }
-            TypeExpr::Union(TypeUnion { docs: _, members }) => {
+            TypeExpr::Union(TypeUnion { docs, members }) => {
+                state.write_u8(6);
+                visit_docs(docs, hash_kind, state);
+                hash_len(members.len(), state);
                 for member in *members {
                     visit_expr(member, hash_kind, state);
                 }
             }
-            TypeExpr::Intersection(TypeIntersection { docs: _, members }) => {
+            TypeExpr::Intersection(TypeIntersection { docs, members }) => {
+                state.write_u8(7);
+                visit_docs(docs, hash_kind, state);
+                hash_len(members.len(), state);
                 for member in *members {
                     visit_expr(member, hash_kind, state);
                 }
@@ -335,7 +489,141 @@ fn hash_type_expr(expr: &TypeExpr, hash_kind: HashKind) -> u64 {
         }
     }
 
-    let mut hasher = DefaultHasher::new();
+    let mut hasher = Fnv1aHasher::new();
     visit_expr(expr, hash_kind, &mut hasher);
     hasher.finish()
 }
+
+/// Hashes a type expression, including the generic arguments of references
+/// to type definitions, so that equal hashes mean the expressions are emitted
+/// the same way.
+pub(crate) fn hash_type_expr_visit(expr: &TypeExpr) -> u64 {
+    hash_type_expr(expr, HashKind::Visit)
+}
+
+/// Hashes a type definition, ignoring the generic arguments of references to
+/// it.
+pub(crate) fn hash_type_def(def: &TypeDefinition) -> u64 {
+    let TypeDefinition {
+        docs: _,
+        path,
+        name: Ident(name),
+        generic_vars,
+        generic_defaults,
+        def,
+        emit_schema: _,
+        interface: _,
+    } = def;
+    let mut hasher = Fnv1aHasher::new();
+    hash_len(path.len(), &mut hasher);
+    for Ident(path_part) in *path {
+        path_part.hash(&mut hasher);
+    }
+    name.hash(&mut hasher);
+    hash_len(generic_vars.len(), &mut hasher);
+    for Ident(generic_var) in *generic_vars {
+        generic_var.hash(&mut hasher);
+    }
+    hash_len(generic_defaults.len(), &mut hasher);
+    for generic_default in *generic_defaults {
+        generic_default.is_some().hash(&mut hasher);
+        if let Some(generic_default) = generic_default {
+            hash_type_expr(generic_default, HashKind::Emit).hash(&mut hasher);
+        }
+    }
+    hash_type_expr(def, HashKind::Emit).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hashes all of the type definitions which the given types depend on,
+/// including everything about them which affects the emitted code.
+pub(crate) fn hash_schema(roots: &[&'static TypeInfo]) -> u64 {
+    let mut hasher = Fnv1aHasher::new();
+    for def in IterDefDeps::new(roots) {
+        // integers are hashed in native byte order, so they are written
+        // explicitly to keep the hash the same on all platforms
+        hasher.write(&hash_def_schema(def).to_le_bytes());
+    }
+    hasher.finish()
+}
+
+/// Hashes a type definition, including everything about it which affects the
+/// emitted code, but only the names of the definitions it references.
+pub(crate) fn hash_def_schema(def: &TypeDefinition) -> u64 {
+    let TypeDefinition {
+        docs,
+        path,
+        name: Ident(name),
+        generic_vars,
+        generic_defaults,
+        def,
+        emit_schema,
+        interface,
+    } = def;
+    let mut hasher = Fnv1aHasher::new();
+    hash_docs(docs, &mut hasher);
+    hash_len(path.len(), &mut hasher);
+    for Ident(path_part) in *path {
+        path_part.hash(&mut hasher);
+    }
+    name.hash(&mut hasher);
+    hash_len(generic_vars.len(), &mut hasher);
+    for Ident(generic_var) in *generic_vars {
+        generic_var.hash(&mut hasher);
+    }
+    hash_len(generic_defaults.len(), &mut hasher);
+    for generic_default in *generic_defaults {
+        generic_default.is_some().hash(&mut hasher);
+        if let Some(generic_default) = generic_default {
+            hasher.write(
+                &hash_type_expr(generic_default, HashKind::Schema)
+                    .to_le_bytes(),
+            );
+        }
+    }
+    hasher.write(&hash_type_expr(def, HashKind::Schema).to_le_bytes());
+    emit_schema.hash(&mut hasher);
+    interface.is_some().hash(&mut hasher);
+    if let Some(interface) = interface {
+        interface.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Hashes the length of a list, written explicitly so that the hash is the
+/// same on all platforms.
+fn hash_len(len: usize, state: &mut Fnv1aHasher) {
+    state.write(&(len as u64).to_le_bytes());
+}
+
+fn hash_docs(docs: &Option<Docs>, state: &mut Fnv1aHasher) {
+    docs.is_some().hash(state);
+    if let Some(Docs(docs)) = docs {
+        docs.hash(state);
+    }
+}
+
+/// A [64-bit FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hasher.
+///
+/// This is used instead of the standard library's `DefaultHasher` since it is
+/// available without `std`.
+struct Fnv1aHasher(u64);
+
+impl Fnv1aHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -27,7 +27,29 @@
 //!
 //! # Features
 //!
+//! * `std` (enabled by default) - Adds [`TypeDef`] impls for types which are only
+//!   in the standard library (such as `HashMap` and `PathBuf`) and the
+//!   functions which write to a [`std::io::Write`].
+//!   Without this feature the crate is `no_std` (but requires `alloc`), and
+//!   definition files can be written to a [`core::fmt::Write`] using
+//!   [`write_definition_file_fmt`].
 //! * `json_value` - Adds [`TypeDef`] impls for JSON value types from `serde_json`.
+//! * `arrayvec` - Adds [`TypeDef`] impls for `ArrayVec` and `ArrayString` from `arrayvec`.
+//! * `smallvec` - Adds [`TypeDef`] impls for `SmallVec` from `smallvec`.
+//! * `chrono` - Adds [`TypeDef`] impls for date and time types from `chrono`.
+//! * `jiff` - Adds [`TypeDef`] impls for `Timestamp`, `Span` and the civil
+//!   `Date`, `Time` and `DateTime` types from `jiff`, which are all `string`
+//!   since `jiff` serializes them as ISO 8601 strings.
+//!   `jiff` requires Rust 1.70 or later, which is newer than the toolchain the
+//!   rest of this crate is tested with.
+//! * `fetch_client` - Adds the [`fetch_client`] module for emitting typed
+//!   `fetch` client functions for types with a `#[type_def(route = "...")]`
+//!   attribute.
+//! * `serde` - Implements `Serialize` for the types in [`type_expr`], so the
+//!   type information can be exported (for example as JSON) for use by other
+//!   code generators, and `Serialize` and `Deserialize` for [`Manifest`].
+//! * `rayon` - Renders type definitions in parallel using `rayon`. The output is
+//!   identical, but this can be faster when emitting a large number of types.
 //!
 //! # Examples
 //!
@@ -209,15 +231,27 @@
 //! ```
 #![warn(rust_2018_idioms, clippy::all, missing_docs)]
 #![deny(clippy::correctness)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 mod emit;
+#[cfg(feature = "fetch_client")]
+pub mod fetch_client;
 mod impls;
 mod iter_def_deps;
 pub mod type_expr;
 
 pub use crate::emit::{
-    write_definition_file, write_definition_file_from_type_infos,
-    DefinitionFileOptions, Stats, TypeDef,
+    schema_hash, write_definition_file_fmt, DefinitionFileOptions, HeaderStyle,
+    LineEnding, Manifest, ManifestDiff, MapStyle, ModuleSystem,
+    SharedDefinitions, Stats, TypeDef,
+};
+#[cfg(feature = "std")]
+pub use crate::emit::{
+    write_changed_definitions, write_definition_file,
+    write_definition_file_from_type_infos, write_definition_file_with_shared,
+    write_type_definition,
 };
 
 /// A derive proc-macro for the [`TypeDef`] trait.
@@ -242,6 +276,176 @@ pub use crate::emit::{
 ///   JSON format matches the JSON format of the field's type. This
 ///   attribute can be used to specify the type definition for a foreign
 ///   type using your own type.
+/// * `#[type_def(literal = ...)]` on a struct or tuple field will use the
+///   given string, number, or boolean literal as the field's TypeScript
+///   type. This is useful for fields which always have the same value,
+///   such as version tags. Number literals are always emitted as `number`
+///   literal types (never `bigint` literals such as `123n`), since
+///   `JSON.parse` reads every JSON number as a `number`.
+/// * `#[type_def(display_from_str)]` on a struct or tuple field will use
+///   `string` as the field's TypeScript type. This is meant for fields which
+///   are serialized using their `Display` impl, such as with
+///   `#[serde_as(as = "DisplayFromStr")]` from
+///   [`serde_with`](https://docs.rs/serde_with/), since the `serde_as`
+///   attribute cannot be read by this macro. For an `Option` field using
+///   `Option<DisplayFromStr>`, use `#[type_def(type_of = "Option<String>")]`
+///   instead.
+/// * `#[type_def(optional)]` on a named struct field makes the field optional
+///   (`"field"?: T`) regardless of its `serde` attributes, such as for a
+///   field which is only sometimes present due to custom serialization.
+///   Conversely, `#[type_def(required)]` makes a field required which would
+///   otherwise be optional, such as one with `#[serde(default)]` which is
+///   always present when serialized.
+/// * `#[type_def(brand)]` on a newtype struct (or a struct with
+///   `#[serde(transparent)]`) emits a branded type such as
+///   `string & { readonly "__brand": "UserId" }` instead of just the inner
+///   type, so that different newtypes over the same type are not
+///   interchangeable in TypeScript. The brand is the type's name qualified
+///   with its namespace, so same-named types in different namespaces are
+///   distinct too. The `__brand` field never exists at runtime.
+/// * `#[type_def(tag_value = "...")]` on an enum variant overrides the
+///   variant's name in the TypeScript definition only, i.e. the key of an
+///   externally tagged variant or the value of the tag field of an internally
+///   or adjacently tagged variant. Unlike `#[serde(rename = "...")]`, this
+///   does not change how the variant is serialized, so it is only useful
+///   where serialization is customized to produce the same value. It is not
+///   valid for untagged enums or `#[serde(other)]` variants.
+/// * `#[type_def(doc = "...")]` on the struct/enum body or on a struct
+///   field adds documentation to the TypeScript definition only. It is
+///   appended after the Rust doc comments, if there are any.
+/// * `#[type_def(example = "...")]` on the struct/enum body or on a struct
+///   field adds a JSDoc `@example` block with the given content to the
+///   TypeScript definition, for documentation generators such as
+///   [TypeDoc](https://typedoc.org/).
+/// * `#[type_def(route = "GET /users/:id")]` on the struct/enum body (with
+///   the `fetch_client` crate feature) also implements
+///   `fetch_client::Route` for the type, making it the request type of the
+///   route. The optional `#[type_def(response = "T")]` sets the Rust type `T`
+///   of the route's JSON response. Each `:param` segment of the path names a
+///   field of the request by its Rust or serialized name, and all fields of
+///   `GET` and `DELETE` requests must be path parameters. The client function
+///   is named after the type in camelCase and can be emitted with
+///   `fetch_client::write_fetch_client`.
+/// * `#[type_def(emit_schema)]` on a struct with named fields also emits a
+///   runtime constant describing the kind of each field, such as
+///   `export const UserSchema = { "name": "string" } as const;`.
+///   Each kind is one of `"string"`, `"number"`, `"boolean"`, `"null"`,
+///   `"array"`, `"object"`, `"union"`, or `"unknown"`, where `null` is ignored
+///   in unions (so an `Option<String>` field has kind `"string"`). Since a
+///   constant has a runtime value, the definition file must be written as a
+///   `.ts` file rather than a `.d.ts` file when this is used.
+/// * `#[type_def(interface)]` on a struct with named fields emits its
+///   definition as an `interface` rather than a `type` alias, and
+///   `#[type_def(type_alias)]` on the struct/enum body always emits a `type`
+///   alias. These override the
+///   [`objects_as_interfaces`](DefinitionFileOptions::objects_as_interfaces)
+///   option for a single type. Flattened fields cannot be used with
+///   `interface`, since the definition would not be an object type.
+///
+/// Types with lifetime parameters, such as structs holding zero-copy fields
+/// like `Cow<'a, str>` or `Cow<'a, [T]>`, are supported since their
+/// TypeScript definition does not depend on the lifetimes. Because all
+/// [`TypeDef`] types must be `'static`, the generated implementation is for
+/// the `'static` instantiation of the type (e.g. `Foo<'static>`).
+///
+/// Flags types from [`bitflags`](https://docs.rs/bitflags/) can derive
+/// [`TypeDef`] by declaring the struct yourself and using the `impl` form of
+/// the `bitflags!` macro, so that the flags are serialized as their integer
+/// bits:
+/// ```
+/// use serde::Serialize;
+/// use typescript_type_def::TypeDef;
+///
+/// #[derive(Clone, Copy, Serialize, TypeDef)]
+/// struct Permissions(u8);
+///
+/// bitflags::bitflags! {
+///     impl Permissions: u8 {
+///         const READ = 1;
+///         const WRITE = 1 << 1;
+///     }
+/// }
+/// ```
+/// For a struct declared by `bitflags!` itself, [`TypeDef`] has to be
+/// implemented manually to match its `serde` implementation, e.g. with
+/// `const INFO: TypeInfo = <String as TypeDef>::INFO;` for `bitflags` 2,
+/// which serializes flags as text such as `"READ | WRITE"` in JSON.
+///
+/// Recursive types, such as `struct Tree<T> { value: T, children:
+/// Vec<Tree<T>> }`, are not supported. A type's [`TypeDef::INFO`] constant
+/// contains references to the constants of the types it uses, so the
+/// constant of a recursive type would depend on itself, which fails to
+/// compile with a cycle error
+/// ([`E0391`](https://doc.rust-lang.org/error_codes/E0391.html)). This
+/// applies equally to non-generic types and to generic types with any number
+/// of type parameters.
+///
+/// Fields or variants which are serialized with the same name, such as after
+/// a `#[serde(rename = "...")]` or `#[serde(rename_all = "...")]`, are
+/// rejected with a compile error since they would produce an invalid type:
+/// ```compile_fail
+/// use serde::Serialize;
+/// use typescript_type_def::TypeDef;
+///
+/// #[derive(Serialize, TypeDef)]
+/// struct Foo {
+///     #[serde(rename = "b")]
+///     a: String,
+///     b: String,
+/// }
+/// ```
+///
+/// Enum representations which serde rejects are also rejected with a compile
+/// error, such as `content` without `tag`:
+/// ```compile_fail
+/// use typescript_type_def::TypeDef;
+///
+/// #[derive(TypeDef)]
+/// #[serde(content = "value")]
+/// enum Foo {
+///     A(String),
+/// }
+/// ```
+/// both `tag` and `untagged`:
+/// ```compile_fail
+/// use typescript_type_def::TypeDef;
+///
+/// #[derive(TypeDef)]
+/// #[serde(tag = "kind", untagged)]
+/// enum Foo {
+///     A { a: String },
+/// }
+/// ```
+/// equal `tag` and `content` values:
+/// ```compile_fail
+/// use typescript_type_def::TypeDef;
+///
+/// #[derive(TypeDef)]
+/// #[serde(tag = "kind", content = "kind")]
+/// enum Foo {
+///     A(String),
+/// }
+/// ```
+/// an internally tagged variant with a field named like the tag:
+/// ```compile_fail
+/// use typescript_type_def::TypeDef;
+///
+/// #[derive(TypeDef)]
+/// #[serde(tag = "kind")]
+/// enum Foo {
+///     A { kind: String },
+/// }
+/// ```
+/// or an internally tagged tuple variant:
+/// ```compile_fail
+/// use typescript_type_def::TypeDef;
+///
+/// #[derive(TypeDef)]
+/// #[serde(tag = "kind")]
+/// enum Foo {
+///     A(String, String),
+/// }
+/// ```
 ///
 /// ## `serde` attribute support
 ///
@@ -255,25 +459,26 @@ pub use crate::emit::{
 /// |:-|:-:|
 /// | [`#[serde(rename = "name")]`](https://serde.rs/container-attrs.html#rename) | ✓ |
 /// | [`#[serde(rename_all = "...")]`](https://serde.rs/container-attrs.html#rename_all) | ✓ |
-/// | [`#[serde(deny_unknown_fields)]`](https://serde.rs/container-attrs.html#deny_unknown_fields) | ? |
+/// | [`#[serde(rename_all_fields = "...")]`](https://serde.rs/container-attrs.html#rename_all_fields) | ✓ |
+/// | [`#[serde(deny_unknown_fields)]`](https://serde.rs/container-attrs.html#deny_unknown_fields) | ✓[^deny_unknown_fields] |
 /// | [`#[serde(tag = "type")]`](https://serde.rs/container-attrs.html#tag) | ✓ |
 /// | [`#[serde(tag = "t", content = "c")]`](https://serde.rs/container-attrs.html#tag--content) | ✓ |
 /// | [`#[serde(untagged)]`](https://serde.rs/container-attrs.html#untagged) | ✓ |
-/// | [`#[serde(bound = "T: MyTrait")]`](https://serde.rs/container-attrs.html#bound) | ? |
-/// | [`#[serde(default)]`](https://serde.rs/container-attrs.html#default) | ? |
-/// | [`#[serde(default = "path")]`](https://serde.rs/container-attrs.html#default--path) | ? |
+/// | [`#[serde(bound = "T: MyTrait")]`](https://serde.rs/container-attrs.html#bound) | ✓[^bound] |
+/// | [`#[serde(default)]`](https://serde.rs/container-attrs.html#default) | ✓ |
+/// | [`#[serde(default = "path")]`](https://serde.rs/container-attrs.html#default--path) | ✓ |
 /// | [`#[serde(remote = "...")]`](https://serde.rs/container-attrs.html#remote) | ✗ |
 /// | [`#[serde(transparent)]`](https://serde.rs/container-attrs.html#transparent) | ✓ |
-/// | [`#[serde(from = "FromType")]`](https://serde.rs/container-attrs.html#from) | ✗ |
-/// | [`#[serde(try_from = "FromType")]`](https://serde.rs/container-attrs.html#try_from) | ✗ |
-/// | [`#[serde(into = "IntoType")]`](https://serde.rs/container-attrs.html#into) | ✗ |
+/// | [`#[serde(from = "FromType")]`](https://serde.rs/container-attrs.html#from) | ✓[^from_into] |
+/// | [`#[serde(try_from = "FromType")]`](https://serde.rs/container-attrs.html#try_from) | ✓[^from_into] |
+/// | [`#[serde(into = "IntoType")]`](https://serde.rs/container-attrs.html#into) | ✓[^from_into] |
 /// | [`#[serde(crate = "...")]`](https://serde.rs/container-attrs.html#crate) | ✗ |
 ///
 /// ### Variant Attributes
 /// | Attribute | Support |
 /// |:-|:-:|
 /// | [`#[serde(rename = "name")]`](https://serde.rs/variant-attrs.html#rename) | ✓ |
-/// | [`#[serde(alias = "name")]`](https://serde.rs/variant-attrs.html#alias) | ? |
+/// | [`#[serde(alias = "name")]`](https://serde.rs/variant-attrs.html#alias) | ✓[^alias] |
 /// | [`#[serde(rename_all = "...")]`](https://serde.rs/variant-attrs.html#rename_all) | ✓ |
 /// | [`#[serde(skip)]`](https://serde.rs/variant-attrs.html#skip) | ✓ |
 /// | [`#[serde(skip_serializing)]`](https://serde.rs/variant-attrs.html#skip_serializing) | ✗ |
@@ -281,28 +486,59 @@ pub use crate::emit::{
 /// | [`#[serde(serialize_with = "path")]`](https://serde.rs/variant-attrs.html#serialize_with) | ✗ |
 /// | [`#[serde(deserialize_with = "path")]`](https://serde.rs/variant-attrs.html#deserialize_with) | ✗ |
 /// | [`#[serde(with = "module")]`](https://serde.rs/variant-attrs.html#with) | ✗ |
-/// | [`#[serde(bound = "T: MyTrait")]`](https://serde.rs/variant-attrs.html#bound) | ? |
+/// | [`#[serde(bound = "T: MyTrait")]`](https://serde.rs/variant-attrs.html#bound) | ✓[^bound] |
 /// | [`#[serde(borrow)]`](https://serde.rs/variant-attrs.html#borrow) | ? |
 /// | [`#[serde(borrow = "'a + 'b + ...")]`](https://serde.rs/variant-attrs.html#borrow) | ? |
-/// | [`#[serde(other)]`](https://serde.rs/variant-attrs.html#other) | ? |
+/// | [`#[serde(other)]`](https://serde.rs/variant-attrs.html#other) | ✓ |
 ///
 /// ### Field Attributes
 /// | Attribute | Support |
 /// |:-|:-:|
 /// | [`#[serde(rename = "name")]`](https://serde.rs/field-attrs.html#rename) | ✓ |
-/// | [`#[serde(alias = "name")]`](https://serde.rs/field-attrs.html#alias) | ? |
+/// | [`#[serde(alias = "name")]`](https://serde.rs/field-attrs.html#alias) | ✓[^alias] |
 /// | [`#[serde(default)]`](https://serde.rs/field-attrs.html#default) | ✓ |
 /// | [`#[serde(default = "path")]`](https://serde.rs/field-attrs.html#default--path) | ✓ |
 /// | [`#[serde(flatten)]`](https://serde.rs/field-attrs.html#flatten) | ✓ |
 /// | [`#[serde(skip)]`](https://serde.rs/field-attrs.html#skip) | ✓ |
 /// | [`#[serde(skip_serializing)]`](https://serde.rs/field-attrs.html#skip_serializing) | ✗ |
 /// | [`#[serde(skip_deserializing)]`](https://serde.rs/field-attrs.html#skip_deserializing) | ✗ |
-/// | [`#[serde(skip_serializing_if = "path")]`](https://serde.rs/field-attrs.html#skip_serializing_if) | ✓ |
+/// | [`#[serde(skip_serializing_if = "path")]`](https://serde.rs/field-attrs.html#skip_serializing_if) | ✓[^skip_serializing_if] |
 /// | [`#[serde(serialize_with = "path")]`](https://serde.rs/field-attrs.html#serialize_with) | ✗ |
 /// | [`#[serde(deserialize_with = "path")]`](https://serde.rs/field-attrs.html#deserialize_with) | ✗ |
-/// | [`#[serde(with = "module")]`](https://serde.rs/field-attrs.html#with) | ✗ |
+/// | [`#[serde(with = "module")]`](https://serde.rs/field-attrs.html#with) | ✓[^with] |
 /// | [`#[serde(borrow)]`](https://serde.rs/field-attrs.html#borrow) | ? |
 /// | [`#[serde(borrow = "'a + 'b + ...")]`](https://serde.rs/field-attrs.html#borrow) | ? |
-/// | [`#[serde(bound = "T: MyTrait")]`](https://serde.rs/field-attrs.html#bound) | ? |
+/// | [`#[serde(bound = "T: MyTrait")]`](https://serde.rs/field-attrs.html#bound) | ✓[^bound] |
 /// | [`#[serde(getter = "...")]`](https://serde.rs/field-attrs.html#getter) | ✗ |
+///
+/// [^alias]: Aliases are only accepted when deserializing, so they are
+/// intentionally ignored and only the serialized name appears in the type
+/// definition.
+///
+/// [^deny_unknown_fields]: The type definition is the same as without this
+/// attribute. TypeScript already reports unknown fields in object literals
+/// assigned to object types, which matches the strictness of this attribute
+/// for most uses.
+///
+/// [^from_into]: The type definition is a reference to the definition of
+/// the given type, which must implement [`TypeDef`], instead of being derived
+/// from the fields or variants of the type itself. If both are given, the
+/// type of `into` is used, since it determines the serialized JSON.
+///
+/// [^skip_serializing_if]: Any predicate makes the field optional, such as
+/// `Vec::is_empty` for a field which is absent when it is empty. With
+/// `Option::is_none`, the field's type is also the inner type of the `Option`
+/// rather than including `null`.
+///
+/// [^with]: Only the `chrono` modules which serialize dates as Unix
+/// timestamps are recognized, i.e. `ts_seconds`, `ts_milliseconds`,
+/// `ts_microseconds`, `ts_nanoseconds` and their `_option` variants (either
+/// imported or qualified with `chrono::serde`), which make the field's type
+/// `number` (or `number | null` for an `Option`). Any other module is
+/// ignored, so `#[type_def(type_of = "...")]` should be used to give the type
+/// of the field's JSON format.
+///
+/// [^bound]: These bounds only apply to the `serde` implementations. The
+/// generated [`TypeDef`] implementation always requires every type parameter
+/// to implement [`TypeDef`].
 pub use typescript_type_def_derive::TypeDef;
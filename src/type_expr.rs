@@ -1,9 +1,39 @@
 //! This module defines structs used to create static descriptions of TypeScript
 //! type definitions.
+//!
+//! # Serialization
+//! With the `serde` feature, these types implement
+//! [`Serialize`](https://docs.rs/serde/latest/serde/trait.Serialize.html), so
+//! other tools can consume the type information (for example as JSON) to
+//! generate code of their own. The serialized form follows the Rust
+//! definitions:
+//! * Structs are serialized as objects with the same field names (`r#type`
+//!   is serialized as `type`).
+//! * Enums are externally tagged, i.e. serialized as an object with the name
+//!   of the variant as its only key, such as `{"Native": {"ref": ...}}`.
+//! * [`Ident`] and [`Docs`] are serialized as plain strings, lists as arrays
+//!   and `Option`s as the value or `null`.
+//! * References, such as [`TypeExpr::Ref`], are serialized as the value they
+//!   refer to, so the definition of a type is repeated wherever it is
+//!   referenced.
+//!
+//! For example, the type info of `Option<String>` is serialized as JSON as
+//! ```json
+//! {"Native": {"ref": {"Union": {"docs": null, "members": [
+//!     {"Ref": {"Native": {"ref": {
+//!         "Name": {"path": [], "name": "string", "generic_args": []}
+//!     }}}},
+//!     {"Name": {"path": [], "name": "null", "generic_args": []}}
+//! ]}}}}
+//! ```
+
+use crate::emit::TypeDef;
+use alloc::{string::String, vec::Vec};
 
 /// A description of the type information required to produce a TypeScript type
 /// definition.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum TypeInfo {
     /// This info describes a "native" TypeScript type which does not require a
     /// type definition.
@@ -19,6 +49,7 @@ pub enum TypeInfo {
 /// TypeScript types. Therefore a definition for them is not emitted, and they
 /// are referenced by their definition.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct NativeTypeInfo {
     /// A type expression describing this native type.
     pub r#ref: TypeExpr,
@@ -29,6 +60,7 @@ pub struct NativeTypeInfo {
 /// Defined types need to have a type definition emitted in the TypeScript
 /// module. They are referenced using their name.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DefinedTypeInfo {
     /// The definition of this type.
     ///
@@ -53,6 +85,7 @@ pub struct DefinedTypeInfo {
 
 /// The TypeScript definition of a type.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeDefinition {
     /// The documentation for this type definition.
     pub docs: Option<Docs>,
@@ -64,8 +97,31 @@ pub struct TypeDefinition {
     ///
     /// If empty, the type does not have generics.
     pub generic_vars: List<Ident>,
+    /// The default values of the generic variables for this type definition.
+    ///
+    /// If not empty, this list should have the same length as `generic_vars`,
+    /// with `None` for each variable without a default. Like `def`, these
+    /// values can reference the generic variables using [`TypeExpr::Name`].
+    pub generic_defaults: List<Option<TypeExpr>>,
     /// The definition of this type.
     pub def: TypeExpr,
+    /// Whether to also emit a `const` object describing the kind of each
+    /// field of this type.
+    ///
+    /// See the `emit_schema` attribute of
+    /// [`#[derive(TypeDef)]`](macro@crate::TypeDef).
+    pub emit_schema: bool,
+    /// Whether to emit this type definition as an `interface` rather than a
+    /// `type` alias.
+    ///
+    /// If `None`, the
+    /// [`objects_as_interfaces`](crate::DefinitionFileOptions::objects_as_interfaces)
+    /// option decides. Only definitions which are object types can be
+    /// emitted as interfaces, so this has no effect on other definitions.
+    ///
+    /// See the `interface` and `type_alias` attributes of
+    /// [`#[derive(TypeDef)]`](macro@crate::TypeDef).
+    pub interface: Option<bool>,
 }
 
 /// A TypeScript type expression.
@@ -74,6 +130,7 @@ pub struct TypeDefinition {
 /// only that which is needed by the types defined in this crate and as produced
 /// by [`#[derive(TypeDef)]`](macro@crate::TypeDef).
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum TypeExpr {
     /// A reference to another type.
     Ref(&'static TypeInfo),
@@ -96,6 +153,7 @@ pub enum TypeExpr {
 /// A reference to a built-in TypeScript type, analogous to a Rust path with
 /// optional generics.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeName {
     /// The namespace path for this type.
     pub path: List<Ident>,
@@ -109,6 +167,7 @@ pub struct TypeName {
 
 /// A TypeScript type-level string literal.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeString {
     /// The documentation for this type string.
     pub docs: Option<Docs>,
@@ -122,6 +181,7 @@ pub struct TypeString {
 /// element can have a distinct type. Values of these types are encoded as
 /// arrays in JSON, which are expected to have a constant length.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeTuple {
     /// The documentation for this tuple.
     pub docs: Option<Docs>,
@@ -134,6 +194,7 @@ pub struct TypeTuple {
 
 /// A TypeScript object type.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeObject {
     /// The documentation for this object.
     pub docs: Option<Docs>,
@@ -145,6 +206,7 @@ pub struct TypeObject {
 
 /// An index signature of a TypeScript object type.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct IndexSignature {
     /// The documentation for this index signature.
     pub docs: Option<Docs>,
@@ -156,10 +218,14 @@ pub struct IndexSignature {
 
 /// A field of a TypeScript object type.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ObjectField {
     /// The documentation for this field.
     pub docs: Option<Docs>,
     /// The name of this field.
+    ///
+    /// The name is always emitted as a quoted string literal, so it does not
+    /// need to be a valid TypeScript identifier.
     pub name: TypeString,
     /// Whether this field is optional or not.
     ///
@@ -168,6 +234,11 @@ pub struct ObjectField {
     /// a value of `undefined`. In JSON, omitted optional fields are omitted
     /// from the object serialization.
     pub optional: bool,
+    /// Whether this field is read-only or not.
+    ///
+    /// This corresponds with the `readonly` prefix on the field name which
+    /// prevents TypeScript code from assigning to the field.
+    pub readonly: bool,
     /// The type of this field.
     pub r#type: TypeExpr,
 }
@@ -179,6 +250,7 @@ pub struct ObjectField {
 /// be a union so the elements may have different runtime types). Values of both
 /// of these types are encoded as arrays in JSON.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeArray {
     /// The documentation for this array.
     pub docs: Option<Docs>,
@@ -188,6 +260,7 @@ pub struct TypeArray {
 
 /// A TypeScript union type.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeUnion {
     /// The documentation for this union.
     pub docs: Option<Docs>,
@@ -206,6 +279,7 @@ pub struct TypeUnion {
 /// be intersected and still be accurately encoded as JSON (the resulting type
 /// being an object with the combined fields of all the intersection members).
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeIntersection {
     /// The documentation for this intersection.
     pub docs: Option<Docs>,
@@ -224,6 +298,7 @@ pub struct TypeIntersection {
 /// valid in TypeScript in order for the resulting TypeScript module to be
 /// valid.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Ident(pub &'static str);
 
 /// A documentation string.
@@ -231,17 +306,76 @@ pub struct Ident(pub &'static str);
 /// The string value should be the plain unformatted documentation without any
 /// `/**` or indentation in it. Lines may be separate by newlines.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Docs(pub &'static str);
 
 /// An alias for lists used in type expressions.
 pub type List<T> = &'static [T];
 
+impl DefinedTypeInfo {
+    /// Returns the name of this type qualified with its namespace path.
+    ///
+    /// See [`TypeDefinition::qualified_name`].
+    pub fn qualified_name(&self) -> String {
+        self.def.qualified_name()
+    }
+
+    /// Returns the documentation for this type, if any.
+    ///
+    /// See [`TypeDefinition::description`].
+    pub fn description(&self) -> Option<&'static str> {
+        self.def.description()
+    }
+}
+
+impl TypeDefinition {
+    /// Returns the name of this type qualified with its namespace path,
+    /// separated by `.`.
+    ///
+    /// The qualified name does not include the root namespace of an emitted
+    /// definition file.
+    pub fn qualified_name(&self) -> String {
+        self.path
+            .iter()
+            .chain(core::iter::once(&self.name))
+            .map(|Ident(part)| *part)
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Returns the documentation for this type definition, if any.
+    pub fn description(&self) -> Option<&'static str> {
+        self.docs.map(|Docs(docs)| docs)
+    }
+}
+
 impl TypeExpr {
     /// A helper function to create a type expression representing just an
     /// identifier.
     pub const fn ident(ident: Ident) -> Self {
         Self::Name(TypeName::ident(ident))
     }
+
+    /// A helper function to create a type expression representing an
+    /// identifier with generic arguments, such as `Readonly<T>`.
+    ///
+    /// See [`TypeName::generic`].
+    pub const fn generic(ident: Ident, generic_args: List<TypeExpr>) -> Self {
+        Self::Name(TypeName::generic(ident, generic_args))
+    }
+
+    /// A helper function to create a type expression referencing the type
+    /// info of the Rust type `T`.
+    ///
+    /// This is the same as `TypeExpr::Ref(&T::INFO)`, but it can be more
+    /// convenient to use for generic arguments, e.g.
+    /// `&[TypeExpr::type_ref::<T>()]`.
+    pub const fn type_ref<T>() -> Self
+    where
+        T: TypeDef + ?Sized,
+    {
+        Self::Ref(&T::INFO)
+    }
 }
 
 impl TypeName {
@@ -253,4 +387,38 @@ impl TypeName {
             generic_args: &[],
         }
     }
+
+    /// A helper function to create a type name representing an identifier
+    /// with generic arguments, such as `Readonly<T>`.
+    ///
+    /// The generic arguments can be given as a slice literal, since it is
+    /// promoted to a `'static` slice when used in the
+    /// [`INFO`](crate::emit::TypeDef::INFO) constant of a generic type:
+    /// ```
+    /// use typescript_type_def::{
+    ///     type_expr::{Ident, NativeTypeInfo, TypeExpr, TypeInfo},
+    ///     TypeDef,
+    /// };
+    ///
+    /// struct Frozen<T>(T);
+    ///
+    /// impl<T> TypeDef for Frozen<T>
+    /// where
+    ///     T: TypeDef,
+    /// {
+    ///     const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
+    ///         r#ref: TypeExpr::generic(
+    ///             Ident("Readonly"),
+    ///             &[TypeExpr::type_ref::<T>()],
+    ///         ),
+    ///     });
+    /// }
+    /// ```
+    pub const fn generic(ident: Ident, generic_args: List<TypeExpr>) -> Self {
+        Self {
+            path: &[],
+            name: ident,
+            generic_args,
+        }
+    }
 }
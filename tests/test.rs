@@ -1,22 +1,124 @@
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use typescript_type_def::{
-    type_expr::{DefinedTypeInfo, Ident, TypeDefinition, TypeExpr, TypeInfo},
-    write_definition_file, DefinitionFileOptions, TypeDef,
+    type_expr::{
+        DefinedTypeInfo, Ident, NativeTypeInfo, TypeDefinition, TypeExpr,
+        TypeInfo, TypeTuple,
+    },
+    write_definition_file, write_definition_file_fmt,
+    write_definition_file_with_shared, write_type_definition,
+    DefinitionFileOptions, HeaderStyle, LineEnding, MapStyle, ModuleSystem,
+    SharedDefinitions, TypeDef,
 };
 
 static TEST_OPTIONS: DefinitionFileOptions<'_> = DefinitionFileOptions {
     header: None,
     root_namespace: Some("types"),
+    strict_empty_objects: false,
+    all_type_names: None,
+    line_ending: LineEnding::Lf,
+    module_system: ModuleSystem::Esm,
+    readonly_arrays: false,
+    inline_single_use: false,
+    by_kind_maps: false,
+    partial_aliases: false,
+    finite_floats: false,
+    external_imports: &[],
+    any_type: None,
+    type_override: None,
+    prettier_ignore: false,
+    header_style: HeaderStyle::Verbatim,
+    max_union_width: None,
+    map_style: MapStyle::Record,
+    utf8_bom: false,
+    tag_aliases: false,
+    package_docs: None,
+    objects_as_interfaces: false,
+    declare_module: None,
+    handler_maps: false,
+    root_namespace_docs: None,
+    generated_from: None,
 };
 
 fn test_emit<T>() -> String
+where
+    T: TypeDef,
+{
+    test_emit_with_options::<T>(TEST_OPTIONS)
+}
+
+fn test_emit_with_options<T>(options: DefinitionFileOptions<'_>) -> String
 where
     T: TypeDef,
 {
     let mut buf = Vec::new();
-    write_definition_file::<_, T>(&mut buf, TEST_OPTIONS).unwrap();
-    String::from_utf8(buf).unwrap()
+    write_definition_file::<_, T>(&mut buf, options).unwrap();
+    let emitted = String::from_utf8(buf).unwrap();
+    check_syntax(&emitted);
+    emitted
+}
+
+/// Checks that emitted TypeScript has terminated strings and comments and
+/// balanced brackets.
+///
+/// This is not a full parser, but it catches emission bugs such as unescaped
+/// comment terminators in docs or missing closing braces.
+fn check_syntax(ts: &str) {
+    let mut brackets = Vec::new();
+    let mut chars = ts.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' | '\'' => loop {
+                match chars.next() {
+                    Some((_, '\\')) => {
+                        chars.next();
+                    }
+                    Some((_, end)) if end == c => break,
+                    Some((_, '\n')) | None => {
+                        panic!("unterminated string at byte {} of:\n{}", i, ts)
+                    }
+                    Some(_) => {}
+                }
+            },
+            '/' if matches!(chars.peek(), Some((_, '/'))) => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                chars.next();
+                let mut prev = None;
+                loop {
+                    match chars.next() {
+                        Some((_, '/')) if prev == Some('*') => break,
+                        Some((_, c)) => prev = Some(c),
+                        None => panic!(
+                            "unterminated comment at byte {} of:\n{}",
+                            i, ts
+                        ),
+                    }
+                }
+            }
+            '(' | '[' | '{' => brackets.push((i, c)),
+            ')' | ']' | '}' => {
+                let open = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                match brackets.pop() {
+                    Some((_, c)) if c == open => {}
+                    _ => panic!("unbalanced `{}` at byte {} of:\n{}", c, i, ts),
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((i, c)) = brackets.pop() {
+        panic!("unclosed `{}` at byte {} of:\n{}", c, i, ts);
+    }
 }
 
 macro_rules! assert_eq_str {
@@ -41,7 +143,10 @@ fn emit() {
                 path: &[],
                 name: Ident("Test"),
                 generic_vars: &[],
+                generic_defaults: &[],
                 def: TypeExpr::Ref(&Inner::INFO),
+                emit_schema: false,
+                interface: None,
             },
             generic_args: &[],
         });
@@ -67,6 +172,126 @@ export namespace types {
     assert_eq!(stats.type_definitions, 2);
 }
 
+#[test]
+fn manual_generics() {
+    #[derive(Serialize)]
+    struct Pair<A, B>(A, B);
+
+    impl<A, B> TypeDef for Pair<A, B>
+    where
+        A: TypeDef,
+        B: TypeDef,
+    {
+        const INFO: TypeInfo = TypeInfo::Defined(DefinedTypeInfo {
+            def: TypeDefinition {
+                docs: None,
+                path: &[],
+                name: Ident("Pair"),
+                generic_vars: &[Ident("A"), Ident("B")],
+                generic_defaults: &[],
+                def: TypeExpr::Tuple(TypeTuple {
+                    docs: None,
+                    elements: &[
+                        TypeExpr::ident(Ident("A")),
+                        TypeExpr::ident(Ident("B")),
+                    ],
+                }),
+                emit_schema: false,
+                interface: None,
+            },
+            generic_args: &[
+                TypeExpr::type_ref::<A>(),
+                TypeExpr::type_ref::<B>(),
+            ],
+        });
+    }
+
+    #[derive(Serialize)]
+    struct Frozen<T>(T);
+
+    impl<T> TypeDef for Frozen<T>
+    where
+        T: TypeDef,
+    {
+        const INFO: TypeInfo = TypeInfo::Native(NativeTypeInfo {
+            r#ref: TypeExpr::generic(
+                Ident("Readonly"),
+                &[TypeExpr::type_ref::<T>()],
+            ),
+        });
+    }
+
+    #[derive(Serialize, TypeDef)]
+    struct Test {
+        a: Pair<String, Frozen<u8>>,
+    }
+
+    assert_eq_str!(
+        test_emit::<Test>(),
+        r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Pair<A, B> = [A, B];
+    export type Test = {
+        "a": types.Pair<string, Readonly<types.U8>>;
+    };
+}
+"#
+    );
+}
+
+#[test]
+fn emit_fmt() {
+    type Test = Vec<Option<usize>>;
+
+    let mut emitted = String::new();
+    let stats =
+        write_definition_file_fmt::<_, Test>(&mut emitted, TEST_OPTIONS)
+            .unwrap();
+    assert_eq_str!(
+        emitted,
+        r#"export default types;
+export namespace types {
+    export type Usize = number;
+}
+"#
+    );
+    assert_eq!(stats.type_definitions, 1);
+}
+
+#[test]
+fn emit_type_definition() {
+    type Test = (Vec<u8>, Option<usize>);
+
+    let mut buf = Vec::new();
+    let stats = write_type_definition::<_, Test>(&mut buf).unwrap();
+    assert_eq_str!(
+        String::from_utf8(buf).unwrap(),
+        r#"export type U8 = number;
+export type Usize = number;
+"#
+    );
+    assert_eq!(stats.type_definitions, 2);
+}
+
+#[test]
+fn unit_in_tuples() {
+    type Test = ((), (u8,), (String, (), bool));
+
+    assert_eq_str!(
+        serde_json::to_string(&((), (1u8,), ("a".to_owned(), (), true)))
+            .unwrap(),
+        r#"[null,[1],["a",null,true]]"#
+    );
+
+    let mut buf = Vec::new();
+    Test::INFO.write_ref_expr(&mut buf, Some("types")).unwrap();
+    assert_eq_str!(
+        String::from_utf8(buf).unwrap(),
+        r#"[null, [types.U8], [string, null, boolean]]"#
+    );
+}
+
 mod derive {
     #![allow(dead_code)]
 
@@ -331,11 +556,10 @@ export namespace types {
     export type Inner = {
         "x": boolean;
     };
-    export type Test = (({
+    export type Test = ({
         "type": "A";
-    } & {
         "a": types.Inner;
-    }) | ({
+    } | ({
         "type": "B";
     } & types.Inner) | {
         "type": "D";
@@ -345,6 +569,48 @@ export namespace types {
             );
         }
 
+        #[test]
+        fn tag_flatten() {
+            #[derive(Serialize, TypeDef)]
+            #[serde(tag = "type")]
+            enum Test {
+                A {
+                    #[serde(flatten)]
+                    inner: Inner,
+                    a: u8,
+                },
+                B,
+            }
+
+            assert_eq_str!(
+                serde_json::to_string(&Test::A { inner: INNER, a: 1 }).unwrap(),
+                r#"{"type":"A","x":true,"a":1}"#
+            );
+            assert_eq_str!(
+                test_emit_with_options::<Test>(DefinitionFileOptions {
+                    by_kind_maps: true,
+                    tag_aliases: true,
+                    ..TEST_OPTIONS
+                }),
+                r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Inner = {
+        "x": boolean;
+    };
+    export type Test = (({
+        "type": "A";
+        "a": types.U8;
+    } & types.Inner) | {
+        "type": "B";
+    });
+    export type TestByKind = { [K in Test["type"]]: Extract<Test, { "type": K }> };
+    export type TestTag = ("A" | "B");
+}
+"#
+            );
+        }
+
         #[test]
         fn tag_content() {
             #[derive(Serialize, TypeDef)]
@@ -398,6 +664,116 @@ export namespace types {
             );
         }
 
+        #[test]
+        fn tag_rename_all() {
+            #[derive(Serialize, TypeDef)]
+            #[serde(tag = "kind", rename_all = "snake_case")]
+            enum Test {
+                FooBar {
+                    #[serde(rename = "type")]
+                    a: Inner,
+                },
+                #[serde(rename_all = "camelCase")]
+                BazQux { some_field: bool },
+            }
+
+            assert_eq_str!(
+                serde_json::to_string(&Test::FooBar { a: INNER }).unwrap(),
+                r#"{"kind":"foo_bar","type":{"x":true}}"#
+            );
+            assert_eq_str!(
+                serde_json::to_string(&Test::BazQux { some_field: true })
+                    .unwrap(),
+                r#"{"kind":"baz_qux","someField":true}"#
+            );
+            assert_eq_str!(
+                test_emit::<Test>(),
+                r#"export default types;
+export namespace types {
+    export type Inner = {
+        "x": boolean;
+    };
+    export type Test = ({
+        "kind": "foo_bar";
+        "type": types.Inner;
+    } | {
+        "kind": "baz_qux";
+        "someField": boolean;
+    });
+}
+"#
+            );
+        }
+
+        #[test]
+        fn tag_content_rename_all() {
+            #[derive(Serialize, TypeDef)]
+            #[serde(tag = "Type", content = "Data", rename_all = "snake_case")]
+            enum Test {
+                FooBar(Inner),
+                BazQux,
+            }
+
+            assert_eq_str!(
+                serde_json::to_string(&Test::FooBar(INNER)).unwrap(),
+                r#"{"Type":"foo_bar","Data":{"x":true}}"#
+            );
+            assert_eq_str!(
+                serde_json::to_string(&Test::BazQux).unwrap(),
+                r#"{"Type":"baz_qux"}"#
+            );
+            assert_eq_str!(
+                test_emit::<Test>(),
+                r#"export default types;
+export namespace types {
+    export type Inner = {
+        "x": boolean;
+    };
+    export type Test = ({
+        "Type": "foo_bar";
+        "Data": types.Inner;
+    } | {
+        "Type": "baz_qux";
+    });
+}
+"#
+            );
+        }
+
+        #[test]
+        fn other() {
+            #[derive(Serialize, TypeDef)]
+            #[serde(tag = "type")]
+            enum Test {
+                A {
+                    a: Inner,
+                },
+                #[serde(other)]
+                Unknown,
+            }
+
+            assert_eq_str!(
+                serde_json::to_string(&Test::Unknown).unwrap(),
+                r#"{"type":"Unknown"}"#
+            );
+            assert_eq_str!(
+                test_emit::<Test>(),
+                r#"export default types;
+export namespace types {
+    export type Inner = {
+        "x": boolean;
+    };
+    export type Test = ({
+        "type": "A";
+        "a": types.Inner;
+    } | {
+        "type": string;
+    });
+}
+"#
+            );
+        }
+
         #[test]
         fn untagged() {
             #[derive(Serialize, TypeDef)]
@@ -480,51 +856,157 @@ export namespace types {
 "#
             );
         }
-    }
 
-    #[test]
-    fn docs() {
-        /// enum `Test`
-        #[derive(Serialize, TypeDef)]
-        enum Test {
-            /// struct variant `Test::A`
-            A {
-                /// struct variant field `Test::A.a`
-                a: Test2,
-            },
-            /// newtype variant `Test::B`
-            B(
-                /// newtype variant field `Test::B.0`
-                String,
-            ),
-            /// tuple variant `Test::C`
-            C(
-                /// tuple variant field `Test::C.0`
-                String,
-                /// tuple variant field `Test::C.1`
-                String,
-            ),
-            /// unit variant `Test::D`
-            D,
-        }
+        #[test]
+        fn none_rename_all() {
+            #[derive(Serialize, TypeDef)]
+            #[serde(rename_all = "snake_case")]
+            enum Test {
+                FooBar {
+                    a: Inner,
+                },
+                BazQux(Inner),
+                #[serde(rename = "other")]
+                Quux,
+            }
 
-        /// struct `Test2`
-        #[derive(Serialize, TypeDef)]
-        struct Test2 {
-            /// field `a` of `Test2`
-            pub a: Test3,
+            assert_eq_str!(
+                serde_json::to_string(&Test::FooBar { a: INNER }).unwrap(),
+                r#"{"foo_bar":{"a":{"x":true}}}"#
+            );
+            assert_eq_str!(
+                serde_json::to_string(&Test::BazQux(INNER)).unwrap(),
+                r#"{"baz_qux":{"x":true}}"#
+            );
+            assert_eq_str!(
+                serde_json::to_string(&Test::Quux).unwrap(),
+                r#""other""#
+            );
+            assert_eq_str!(
+                test_emit::<Test>(),
+                r#"export default types;
+export namespace types {
+    export type Inner = {
+        "x": boolean;
+    };
+    export type Test = ({
+        "foo_bar": {
+            "a": types.Inner;
+        };
+    } | {
+        "baz_qux": types.Inner;
+    } | "other");
+}
+"#
+            );
         }
 
-        /// struct `Test3`
-        #[derive(Serialize, TypeDef)]
-        #[type_def(namespace = "test")]
-        struct Test3 {
-            /// field `a` of `Test3`
-            pub a: String,
-        }
+        #[test]
+        fn tag_value() {
+            #[derive(Serialize, TypeDef)]
+            #[serde(tag = "type")]
+            enum Test {
+                #[type_def(tag_value = "a")]
+                A {
+                    a: Inner,
+                },
+                B(Inner),
+                #[type_def(tag_value = "legacy-d")]
+                D,
+            }
 
-        assert_eq_str!(
-            test_emit::<Test>(),
+            #[derive(Serialize, TypeDef)]
+            enum Test2 {
+                #[type_def(tag_value = "a")]
+                A(Inner),
+                B,
+            }
+
+            assert_eq_str!(
+                serde_json::to_string(&Test::A { a: INNER }).unwrap(),
+                r#"{"type":"A","a":{"x":true}}"#
+            );
+            assert_eq_str!(
+                serde_json::to_string(&Test::D).unwrap(),
+                r#"{"type":"D"}"#
+            );
+            assert_eq_str!(
+                test_emit::<Test>(),
+                r#"export default types;
+export namespace types {
+    export type Inner = {
+        "x": boolean;
+    };
+    export type Test = ({
+        "type": "a";
+        "a": types.Inner;
+    } | ({
+        "type": "B";
+    } & types.Inner) | {
+        "type": "legacy-d";
+    });
+}
+"#
+            );
+            assert_eq_str!(
+                test_emit::<Test2>(),
+                r#"export default types;
+export namespace types {
+    export type Inner = {
+        "x": boolean;
+    };
+    export type Test2 = ({
+        "a": types.Inner;
+    } | "B");
+}
+"#
+            );
+        }
+    }
+
+    #[test]
+    fn docs() {
+        /// enum `Test`
+        #[derive(Serialize, TypeDef)]
+        enum Test {
+            /// struct variant `Test::A`
+            A {
+                /// struct variant field `Test::A.a`
+                a: Test2,
+            },
+            /// newtype variant `Test::B`
+            B(
+                /// newtype variant field `Test::B.0`
+                String,
+            ),
+            /// tuple variant `Test::C`
+            C(
+                /// tuple variant field `Test::C.0`
+                String,
+                /// tuple variant field `Test::C.1`
+                String,
+            ),
+            /// unit variant `Test::D`
+            D,
+        }
+
+        /// struct `Test2`
+        #[derive(Serialize, TypeDef)]
+        struct Test2 {
+            /// field `a` of `Test2`
+            pub a: Test3,
+        }
+
+        /// struct `Test3`
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "test")]
+        struct Test3 {
+            /// field `a` of `Test3`
+            pub a: String,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
             r#"export default types;
 export namespace types {
     export namespace test {
@@ -589,6 +1071,213 @@ export namespace types {
         );
     }
 
+    #[test]
+    fn tagged_variant_docs() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(tag = "type")]
+        enum Test {
+            /// struct variant `Test::A`
+            A { a: String },
+            /// unit variant `Test::B`
+            B,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = ({
+
+        /**
+         * struct variant `Test::A`
+         */
+        "type": "A";
+        "a": string;
+    } | {
+
+        /**
+         * unit variant `Test::B`
+         */
+        "type": "B";
+    });
+}
+"#
+        );
+    }
+
+    #[test]
+    fn type_def_doc() {
+        /// struct `Test`
+        #[derive(Serialize, TypeDef)]
+        #[type_def(doc = "only in TypeScript")]
+        struct Test {
+            #[type_def(doc = "field `a` of `Test`")]
+            a: String,
+            /// field `b` of `Test`
+            b: String,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+
+    /**
+     * struct `Test`
+     * 
+     * only in TypeScript
+     */
+    export type Test = {
+
+        /**
+         * field `a` of `Test`
+         */
+        "a": string;
+
+        /**
+         * field `b` of `Test`
+         */
+        "b": string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn type_def_example() {
+        /// struct `Test`
+        #[derive(Serialize, TypeDef)]
+        #[type_def(example = "{ \"a\": \"foo\" }")]
+        struct Test {
+            #[type_def(example = "\"foo\"")]
+            a: String,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+
+    /**
+     * struct `Test`
+     * 
+     * @example
+     * { "a": "foo" }
+     */
+    export type Test = {
+
+        /**
+         * @example
+         * "foo"
+         */
+        "a": string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn defined_type_info_metadata() {
+        /// struct `Test`
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "x.y")]
+        struct Test {
+            a: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test2 {
+            a: String,
+        }
+
+        let info = match Test::INFO {
+            TypeInfo::Defined(info) => info,
+            TypeInfo::Native(_) => panic!("expected defined type info"),
+        };
+        assert_eq!(info.qualified_name(), "x.y.Test");
+        assert_eq!(info.description(), Some("struct `Test`"));
+
+        let info = match Test2::INFO {
+            TypeInfo::Defined(info) => info,
+            TypeInfo::Native(_) => panic!("expected defined type info"),
+        };
+        assert_eq!(info.qualified_name(), "Test2");
+        assert_eq!(info.description(), None);
+    }
+
+    #[test]
+    fn literal() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[type_def(literal = "v1")]
+            version: &'static str,
+            #[type_def(literal = 42)]
+            answer: u8,
+            #[type_def(literal = true)]
+            enabled: bool,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                version: "v1",
+                answer: 42,
+                enabled: true,
+            })
+            .unwrap(),
+            r#"{"version":"v1","answer":42,"enabled":true}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = {
+        "version": "v1";
+        "answer": 42;
+        "enabled": true;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn display_from_str() {
+        fn display<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: fmt::Display,
+            S: serde::Serializer,
+        {
+            serializer.collect_str(value)
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[serde(serialize_with = "display")]
+            #[type_def(display_from_str)]
+            id: u64,
+            count: u64,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test { id: 42, count: 1 }).unwrap(),
+            r#"{"id":"42","count":1}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U64 = number;
+    export type Test = {
+        "id": string;
+        "count": types.U64;
+    };
+}
+"#
+        );
+    }
+
     #[test]
     fn raw_idents() {
         #[allow(non_camel_case_types)]
@@ -656,6 +1345,52 @@ export namespace types {
         );
     }
 
+    #[test]
+    fn namespace_references() {
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "x.y")]
+        struct Leaf {
+            a: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "x.y")]
+        struct Node<T> {
+            value: T,
+            leaves: Vec<Leaf>,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "z")]
+        struct Root {
+            a: Node<Node<Leaf>>,
+        }
+
+        assert_eq_str!(
+            test_emit::<Root>(),
+            r#"export default types;
+export namespace types {
+    export namespace x.y {
+        export type Leaf = {
+            "a": string;
+        };
+    }
+    export namespace x.y {
+        export type Node<T> = {
+            "value": T;
+            "leaves": (types.x.y.Leaf)[];
+        };
+    }
+    export namespace z {
+        export type Root = {
+            "a": types.x.y.Node<types.x.y.Node<types.x.y.Leaf>>;
+        };
+    }
+}
+"#
+        );
+    }
+
     #[test]
     fn generics() {
         #[derive(Serialize, TypeDef)]
@@ -705,26 +1440,3328 @@ export namespace types {
     }
 
     #[test]
-    fn default() {
+    fn serde_bound() {
+        trait Id {
+            type Raw: Serialize;
+        }
+
         #[derive(Serialize, TypeDef)]
-        struct Test {
-            #[serde(default)]
-            a: String,
-            #[serde(default = "default_b")]
-            b: String,
+        struct UserId;
+
+        impl Id for UserId {
+            type Raw = usize;
         }
 
-        fn default_b() -> String {
+        #[derive(Serialize, TypeDef)]
+        #[serde(bound = "T: Serialize")]
+        struct Test<T, U>
+        where
+            U: Id,
+        {
+            a: T,
+            #[serde(bound(serialize = "U: Id"))]
+            #[type_def(type_of = "usize")]
+            b: U::Raw,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test::<bool, UserId> { a: true, b: 1 })
+                .unwrap(),
+            r#"{"a":true,"b":1}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test<bool, UserId>>(),
+            r#"export default types;
+export namespace types {
+    export type Usize = number;
+    export type UserId = null;
+    export type Test<T, U> = {
+        "a": T;
+        "b": types.Usize;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn generic_namespaces() {
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "models")]
+        struct User {
+            name: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "api")]
+        struct Page<T> {
+            items: Vec<T>,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Page<User>,
+            b: Page<Page<User>>,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export namespace models {
+        export type User = {
+            "name": string;
+        };
+    }
+    export namespace api {
+        export type Page<T> = {
+            "items": (T)[];
+        };
+    }
+    export type Test = {
+        "a": types.api.Page<types.models.User>;
+        "b": types.api.Page<types.api.Page<types.models.User>>;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn generic_defaults() {
+        #[derive(Serialize, TypeDef)]
+        struct Wrapper<T = String> {
+            a: T,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Wrapper,
+            b: Wrapper<usize>,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Wrapper<T = string> = {
+        "a": T;
+    };
+    export type Usize = number;
+    export type Test = {
+        "a": types.Wrapper<string>;
+        "b": types.Wrapper<types.Usize>;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn borrowed_lifetimes() {
+        use std::borrow::Cow;
+
+        #[derive(Serialize, TypeDef)]
+        struct Test<'a> {
+            a: Cow<'a, str>,
+            b: Cow<'a, [u8]>,
+            c: Option<Cow<'a, str>>,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test2 {
+            a: Test<'static>,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test2>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a": string;
+        "b": (types.U8)[];
+        "c": (string | null);
+    };
+    export type Test2 = {
+        "a": types.Test;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn static_cows() {
+        use std::borrow::Cow;
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Cow<'static, str>,
+            b: Cow<'static, [u32]>,
+            c: Cow<'static, [Cow<'static, str>]>,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U32 = number;
+    export type Test = {
+        "a": string;
+        "b": (types.U32)[];
+        "c": (string)[];
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn default() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[serde(default)]
+            a: String,
+            #[serde(default = "default_b")]
+            b: String,
+        }
+
+        fn default_b() -> String {
             "foobar".to_owned()
         }
 
         assert_eq_str!(
-            test_emit::<Test>(),
-            r#"export default types;
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = {
+        "a"?: string;
+        "b"?: string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn default_option() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[serde(default)]
+            a: Option<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            b: Option<String>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test { a: None, b: None }).unwrap(),
+            r#"{"a":null}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = {
+        "a"?: (string | null);
+        "b"?: string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn skip_serializing_none() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            a: Option<String>,
+            #[serde(skip_serializing_if = "std::option::Option::is_none")]
+            b: Option<String>,
+            c: Option<String>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: None,
+                b: None,
+                c: None,
+            })
+            .unwrap(),
+            r#"{"c":null}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = {
+        "a"?: string;
+        "b"?: string;
+        "c": (string | null);
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn skip_serializing_empty() {
+        fn is_zero(value: &u8) -> bool {
+            *value == 0
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            a: Vec<String>,
+            #[serde(skip_serializing_if = "HashMap::is_empty")]
+            b: HashMap<String, bool>,
+            #[serde(skip_serializing_if = "is_zero")]
+            c: u8,
+            #[serde(skip_serializing_if = "Option::is_some")]
+            d: Option<String>,
+            e: Vec<String>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: Vec::new(),
+                b: HashMap::new(),
+                c: 0,
+                d: Some("d".to_owned()),
+                e: Vec::new(),
+            })
+            .unwrap(),
+            r#"{"e":[]}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a"?: (string)[];
+        "b"?: Record<string, boolean>;
+        "c"?: types.U8;
+        "d"?: (string | null);
+        "e": (string)[];
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn optional_override() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[type_def(optional)]
+            a: String,
+            #[serde(default)]
+            #[type_def(required)]
+            b: Vec<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[type_def(required)]
+            c: Option<String>,
+            #[serde(default)]
+            d: u8,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a"?: string;
+        "b": (string)[];
+        "c": (string | null);
+        "d"?: types.U8;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn quoted_keys() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(rename_all = "kebab-case")]
+        struct Test {
+            foo_bar: String,
+            #[serde(rename = "123")]
+            b: String,
+            #[serde(rename = "with space")]
+            c: String,
+            #[serde(rename = "with\"quote")]
+            d: String,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                foo_bar: "a".to_owned(),
+                b: "b".to_owned(),
+                c: "c".to_owned(),
+                d: "d".to_owned(),
+            })
+            .unwrap(),
+            r#"{"foo-bar":"a","123":"b","with space":"c","with\"quote":"d"}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = {
+        "foo-bar": string;
+        "123": string;
+        "with space": string;
+        "with\"quote": string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn pascal_case_keys() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(rename_all = "PascalCase")]
+        struct Test {
+            field_name: String,
+            #[serde(rename = "Other-Key")]
+            other_key: String,
+            nested: Nested,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[serde(rename_all = "PascalCase")]
+        struct Nested {
+            a_b: bool,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Nested = {
+        "AB": boolean;
+    };
+    export type Test = {
+        "FieldName": string;
+        "Other-Key": string;
+        "Nested": types.Nested;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn rename_all_cases() {
+        macro_rules! test_case {
+            ($rule:tt, $field:literal, $variant:literal) => {{
+                #[derive(Serialize, TypeDef)]
+                #[serde(rename_all = $rule)]
+                struct Fields {
+                    foo_bar_baz: bool,
+                }
+
+                #[derive(Serialize, TypeDef)]
+                #[serde(rename_all = $rule)]
+                enum Variants {
+                    FooBarBaz,
+                }
+
+                assert_eq_str!(
+                    serde_json::to_string(&Fields { foo_bar_baz: true })
+                        .unwrap(),
+                    &format!(r#"{{"{}":true}}"#, $field)
+                );
+                assert_eq_str!(
+                    serde_json::to_string(&Variants::FooBarBaz).unwrap(),
+                    &format!(r#""{}""#, $variant)
+                );
+                assert_eq_str!(
+                    test_emit::<(Fields, Variants)>(),
+                    &format!(
+                        r#"export default types;
+export namespace types {{
+    export type Fields = {{
+        "{}": boolean;
+    }};
+    export type Variants = "{}";
+}}
+"#,
+                        $field, $variant
+                    )
+                );
+            }};
+        }
+
+        test_case!("lowercase", "foo_bar_baz", "foobarbaz");
+        test_case!("UPPERCASE", "FOO_BAR_BAZ", "FOOBARBAZ");
+        test_case!("PascalCase", "FooBarBaz", "FooBarBaz");
+        test_case!("camelCase", "fooBarBaz", "fooBarBaz");
+        test_case!("snake_case", "foo_bar_baz", "foo_bar_baz");
+        test_case!("SCREAMING_SNAKE_CASE", "FOO_BAR_BAZ", "FOO_BAR_BAZ");
+        test_case!("kebab-case", "foo-bar-baz", "foo-bar-baz");
+        test_case!("SCREAMING-KEBAB-CASE", "FOO-BAR-BAZ", "FOO-BAR-BAZ");
+    }
+
+    #[test]
+    fn rename_all_fields() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(rename_all_fields = "camelCase")]
+        enum Test {
+            A {
+                foo_bar: bool,
+            },
+            #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+            B {
+                foo_bar: bool,
+            },
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test::A { foo_bar: true }).unwrap(),
+            r#"{"A":{"fooBar":true}}"#
+        );
+        assert_eq_str!(
+            serde_json::to_string(&Test::B { foo_bar: true }).unwrap(),
+            r#"{"B":{"FOO_BAR":true}}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = ({
+        "A": {
+            "fooBar": boolean;
+        };
+    } | {
+        "B": {
+            "FOO_BAR": boolean;
+        };
+    });
+}
+"#
+        );
+    }
+
+    #[test]
+    fn strict_empty_objects() {
+        #[derive(Serialize, TypeDef)]
+        struct Empty {}
+
+        #[derive(Serialize, TypeDef)]
+        enum Test {
+            A(Empty),
+            B {},
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                strict_empty_objects: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Empty = Record<string, never>;
+    export type Test = ({
+        "A": types.Empty;
+    } | {
+        "B": Record<string, never>;
+    });
+}
+"#
+        );
+    }
+
+    #[test]
+    fn all_type_names() {
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "x")]
+        struct Inner {
+            a: usize,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Inner,
+        }
+
+        let mut buf = Vec::new();
+        let options = DefinitionFileOptions {
+            all_type_names: Some("allTypeNames"),
+            ..TEST_OPTIONS
+        };
+        let stats =
+            write_definition_file::<_, Test>(&mut buf, options).unwrap();
+        let result = String::from_utf8(buf).unwrap();
+
+        assert_eq!(stats.type_names, ["Usize", "x.Inner", "Test"]);
+        assert_eq_str!(
+            result,
+            r#"export default types;
+export namespace types {
+    export type Usize = number;
+    export namespace x {
+        export type Inner = {
+            "a": types.Usize;
+        };
+    }
+    export type Test = {
+        "a": types.x.Inner;
+    };
+}
+export const allTypeNames = ["Usize", "x.Inner", "Test"] as const;
+"#
+        );
+    }
+
+    #[test]
+    fn any_type() {
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "x")]
+        struct Inner<T> {
+            a: T,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Inner<usize>,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                any_type: Some("AnyType"),
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Usize = number;
+    export namespace x {
+        export type Inner<T> = {
+            "a": T;
+        };
+    }
+    export type Test = {
+        "a": types.x.Inner<types.Usize>;
+    };
+    export type AnyType = (types.Usize | types.Test);
+}
+"#
+        );
+    }
+
+    #[test]
+    fn transparent() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(transparent)]
+        struct Named {
+            a: Vec<String>,
+            #[serde(skip)]
+            b: PhantomData<()>,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[serde(transparent)]
+        struct Tuple(Named);
+
+        assert_eq_str!(
+            serde_json::to_string(&Tuple(Named {
+                a: vec!["foo".to_owned()],
+                b: PhantomData,
+            }))
+            .unwrap(),
+            r#"["foo"]"#
+        );
+        assert_eq_str!(
+            test_emit::<Tuple>(),
+            r#"export default types;
+export namespace types {
+    export type Named = (string)[];
+    export type Tuple = types.Named;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn from_into() {
+        #[derive(Serialize, TypeDef)]
+        struct Wire {
+            id: String,
+        }
+
+        #[derive(Clone, Serialize, TypeDef)]
+        #[serde(into = "Wire")]
+        struct Test {
+            id: u64,
+        }
+
+        impl From<Test> for Wire {
+            fn from(Test { id }: Test) -> Self {
+                Self { id: id.to_string() }
+            }
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test { id: 1 }).unwrap(),
+            r#"{"id":"1"}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Wire = {
+        "id": string;
+    };
+    export type Test = types.Wire;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn bitflags() {
+        #[derive(Clone, Copy, Serialize, TypeDef)]
+        struct Flags(u32);
+
+        ::bitflags::bitflags! {
+            impl Flags: u32 {
+                const A = 1;
+                const B = 1 << 1;
+            }
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            flags: Flags,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                flags: Flags::A | Flags::B,
+            })
+            .unwrap(),
+            r#"{"flags":3}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U32 = number;
+    export type Flags = types.U32;
+    export type Test = {
+        "flags": types.Flags;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn reverse() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: std::cmp::Reverse<u8>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: std::cmp::Reverse(1),
+            })
+            .unwrap(),
+            r#"{"a":1}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a": types.U8;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn phantom_pinned() {
+        use std::marker::PhantomPinned;
+
+        fn serialize_unit<S>(
+            _: &PhantomPinned,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serializer.serialize_unit()
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: u8,
+            #[serde(serialize_with = "serialize_unit")]
+            b: PhantomPinned,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: 1,
+                b: PhantomPinned,
+            })
+            .unwrap(),
+            r#"{"a":1,"b":null}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a": types.U8;
+        "b": null;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn tuple_struct() {
+        #[derive(Serialize, TypeDef)]
+        struct Rgb(u8, u8, u8);
+
+        assert_eq_str!(
+            serde_json::to_string(&Rgb(1, 2, 3)).unwrap(),
+            r#"[1,2,3]"#
+        );
+        assert_eq_str!(
+            test_emit::<Rgb>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Rgb = [types.U8, types.U8, types.U8];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn struct_arities() {
+        #[derive(Serialize, TypeDef)]
+        struct Unit;
+
+        #[derive(Serialize, TypeDef)]
+        struct Newtype(u8);
+
+        #[derive(Serialize, TypeDef)]
+        struct Tuple(u8, String);
+
+        #[derive(Serialize, TypeDef)]
+        struct Skipped(u8, #[serde(skip)] String);
+
+        #[derive(Serialize, TypeDef)]
+        enum Variants {
+            A(u8, #[serde(skip)] String),
+            B(u8),
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Unit,
+            b: Newtype,
+            c: Tuple,
+            d: Skipped,
+            e: Variants,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: Unit,
+                b: Newtype(1),
+                c: Tuple(2, "foo".to_owned()),
+                d: Skipped(3, "bar".to_owned()),
+                e: Variants::A(4, "baz".to_owned()),
+            })
+            .unwrap(),
+            r#"{"a":null,"b":1,"c":[2,"foo"],"d":[3],"e":{"A":[4]}}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Unit = null;
+    export type U8 = number;
+    export type Newtype = types.U8;
+    export type Tuple = [types.U8, string];
+    export type Skipped = [types.U8];
+    export type Variants = ({
+        "A": [types.U8];
+    } | {
+        "B": types.U8;
+    });
+    export type Test = {
+        "a": types.Unit;
+        "b": types.Newtype;
+        "c": types.Tuple;
+        "d": types.Skipped;
+        "e": types.Variants;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn large_array() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: [u8; 32],
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            &format!(
+                r#"export default types;
+export namespace types {{
+    export type U8 = number;
+    export type Test = {{
+        "a": [{}];
+    }};
+}}
+"#,
+                ["types.U8"; 32].join(", ")
+            )
+        );
+    }
+
+    #[test]
+    fn static_references() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: &'static str,
+            b: Option<&'static str>,
+            c: &'static [u8],
+            d: Option<&'static [&'static str]>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: "foo",
+                b: None,
+                c: &[1, 2],
+                d: Some(&["bar"]),
+            })
+            .unwrap(),
+            r#"{"a":"foo","b":null,"c":[1,2],"d":["bar"]}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a": string;
+        "b": (string | null);
+        "c": (types.U8)[];
+        "d": ((string)[] | null);
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn readonly_arrays() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Vec<String>,
+            b: &'static [u8],
+            c: Option<Vec<Vec<bool>>>,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                readonly_arrays: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a": readonly (string)[];
+        "b": readonly (types.U8)[];
+        "c": (readonly (readonly (boolean)[])[] | null);
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn readonly_tuples() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: [u8; 2],
+            b: (String, bool),
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                readonly_arrays: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a": readonly [types.U8, types.U8];
+        "b": readonly [string, boolean];
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn readonly_index_signatures() {
+        #[derive(Serialize, TypeDef, PartialEq, Eq, PartialOrd, Ord)]
+        enum Key {
+            A,
+            B,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Inner {
+            a: u8,
+            #[serde(flatten)]
+            extra: HashMap<String, u8>,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: HashMap<String, (u8, bool)>,
+            b: std::collections::BTreeMap<Key, bool>,
+            c: Inner,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                readonly_arrays: true,
+                map_style: MapStyle::IndexSignature,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Key = ("A" | "B");
+    export type Inner = {
+        readonly [key:string]:types.U8;
+        "a": types.U8;
+    };
+    export type Test = {
+        "a": {
+            readonly [key:string]:readonly [types.U8, boolean];
+        };
+        "b": {
+            readonly [key in types.Key]:boolean;
+        };
+        "c": types.Inner;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn readonly_records() {
+        #[derive(Serialize, TypeDef, PartialEq, Eq, PartialOrd, Ord)]
+        enum Key {
+            A,
+            B,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: HashMap<String, Vec<u8>>,
+            b: std::collections::BTreeMap<Key, bool>,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                readonly_arrays: true,
+                map_style: MapStyle::Record,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Key = ("A" | "B");
+    export type Test = {
+        "a": Readonly<Record<string, readonly (types.U8)[]>>;
+        "b": Readonly<Record<types.Key, boolean>>;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn option_in_tuple() {
+        #[derive(Serialize, TypeDef)]
+        struct Test(Option<u32>, String, (Option<bool>, Option<Vec<u8>>));
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U32 = number;
+    export type U8 = number;
+    export type Test = [(types.U32 | null), string, [(boolean | null), ((types.U8)[] | null)]];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn map_style() {
+        #[derive(Serialize, TypeDef, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        enum Key {
+            A,
+            B,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: HashMap<String, Key>,
+            b: std::collections::BTreeMap<String, Vec<Key>>,
+            c: std::collections::BTreeMap<u32, bool>,
+            d: HashMap<Key, bool>,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Key = ("A" | "B");
+    export type U32 = number;
+    export type Test = {
+        "a": Record<string, types.Key>;
+        "b": Record<string, (types.Key)[]>;
+        "c": Record<types.U32, boolean>;
+        "d": Record<types.Key, boolean>;
+    };
+}
+"#
+        );
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                map_style: MapStyle::IndexSignature,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Key = ("A" | "B");
+    export type U32 = number;
+    export type Test = {
+        "a": {
+            [key:string]:types.Key;
+        };
+        "b": {
+            [key:string]:(types.Key)[];
+        };
+        "c": {
+            [key:number]:boolean;
+        };
+        "d": {
+            [key in types.Key]:boolean;
+        };
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn inline_single_use() {
+        /// An inlined type.
+        #[derive(Serialize, TypeDef)]
+        struct Inner {
+            x: bool,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Shared {
+            y: bool,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Wrapper<T> {
+            value: T,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Inner,
+            b: Shared,
+            c: Option<Shared>,
+            d: Wrapper<String>,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                inline_single_use: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Shared = {
+        "y": boolean;
+    };
+    export type Wrapper<T> = {
+        "value": T;
+    };
+    export type Test = {
+        "a": 
+        /**
+         * An inlined type.
+         */
+        {
+            "x": boolean;
+        };
+        "b": types.Shared;
+        "c": (types.Shared | null);
+        "d": types.Wrapper<string>;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn max_union_width() {
+        #[derive(Serialize, TypeDef)]
+        enum Letters {
+            A,
+            B,
+            C,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        enum Other {
+            X,
+            Y,
+            Z,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Letters,
+            b: Option<bool>,
+            c: Other,
+            d: Other,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                inline_single_use: true,
+                max_union_width: Some(2),
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Other = ("X" | "Y" | "Z");
+    export type Test = {
+        "a": types.TestUnion1;
+        "b": (boolean | null);
+        "c": types.Other;
+        "d": types.Other;
+    };
+    export type TestUnion1 = ("A" | "B" | "C");
+}
+"#
+        );
+    }
+
+    #[test]
+    fn max_union_width_shared() {
+        use std::ops::Bound;
+
+        #[derive(Serialize, TypeDef)]
+        struct A {
+            a: Bound<u8>,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct B {
+            b: Bound<u8>,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: A,
+            b: B,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                max_union_width: Some(2),
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type A = {
+        "a": types.AUnion1;
+    };
+    export type AUnion1 = ({
+        "Included": types.U8;
+    } | {
+        "Excluded": types.U8;
+    } | "Unbounded");
+    export type B = {
+        "b": types.BUnion1;
+    };
+    export type BUnion1 = ({
+        "Included": types.U8;
+    } | {
+        "Excluded": types.U8;
+    } | "Unbounded");
+    export type Test = {
+        "a": types.A;
+        "b": types.B;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn emit_schema() {
+        #[derive(Serialize, TypeDef)]
+        struct Inner {
+            c: Vec<u8>,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[type_def(emit_schema)]
+        struct Test {
+            a: Option<String>,
+            b: u32,
+            #[serde(rename = "D")]
+            d: bool,
+            e: Result<String, usize>,
+            #[serde(flatten)]
+            inner: Inner,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Inner = {
+        "c": (types.U8)[];
+    };
+    export type U32 = number;
+    export type Usize = number;
+    export type Test = (types.Inner & {
+        "a": (string | null);
+        "b": types.U32;
+        "D": boolean;
+        "e": ({
+            "Ok": string;
+        } | {
+            "Err": types.Usize;
+        });
+    });
+    export const TestSchema = {
+        "c": "array",
+        "a": "string",
+        "b": "number",
+        "D": "boolean",
+        "e": "object",
+    } as const;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn emit_schema_type_override() {
+        fn type_override(def: &'static TypeDefinition) -> Option<TypeExpr> {
+            match def.name {
+                Ident("U32") => Some(TypeExpr::ident(Ident("string"))),
+                _ => None,
+            }
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[type_def(emit_schema)]
+        struct Test {
+            a: u8,
+            b: u32,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                type_override: Some(type_override),
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type U32 = string;
+    export type Test = {
+        "a": types.U8;
+        "b": types.U32;
+    };
+    export const TestSchema = {
+        "a": "number",
+        "b": "string",
+    } as const;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn schema_hash() {
+        use typescript_type_def::schema_hash;
+
+        mod v1 {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Inner {
+                pub a: String,
+            }
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Test {
+                pub inner: Inner,
+            }
+        }
+
+        mod v2 {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Inner {
+                /// docs
+                pub a: String,
+            }
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Test {
+                pub inner: Inner,
+            }
+        }
+
+        mod v3 {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Inner {
+                pub a: Option<String>,
+            }
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Test {
+                pub inner: Inner,
+            }
+        }
+
+        assert_eq!(schema_hash::<v1::Test>(), schema_hash::<v1::Test>());
+        assert_eq!(schema_hash::<v1::Test>(), schema_hash::<&v1::Test>());
+        assert_ne!(schema_hash::<v1::Test>(), schema_hash::<v1::Inner>());
+        assert_ne!(schema_hash::<v1::Test>(), schema_hash::<v2::Test>());
+        assert_ne!(schema_hash::<v1::Test>(), schema_hash::<v3::Test>());
+    }
+
+    #[test]
+    fn schema_hash_shapes() {
+        use typescript_type_def::schema_hash;
+
+        mod vec {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Foo {
+                pub a: Vec<String>,
+            }
+        }
+
+        mod array {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Foo {
+                pub a: [String; 1],
+            }
+        }
+
+        mod option {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Foo {
+                pub a: Option<String>,
+            }
+        }
+
+        mod tuple {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Foo {
+                pub a: (String, ()),
+            }
+        }
+
+        assert_ne!(schema_hash::<vec::Foo>(), schema_hash::<array::Foo>());
+        assert_ne!(schema_hash::<option::Foo>(), schema_hash::<tuple::Foo>());
+    }
+
+    #[test]
+    fn changed_definitions() {
+        use typescript_type_def::{write_changed_definitions, Manifest};
+
+        mod v1 {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Inner {
+                pub a: String,
+            }
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Old {
+                pub b: bool,
+            }
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Test {
+                pub inner: Inner,
+                pub old: Old,
+            }
+        }
+
+        mod v2 {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Inner {
+                pub a: Vec<String>,
+            }
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Test {
+                pub inner: Inner,
+                pub old: bool,
+            }
+        }
+
+        let mut buf = Vec::new();
+        let v1_diff = write_changed_definitions(
+            &mut buf,
+            TEST_OPTIONS,
+            &[&v1::Test::INFO],
+            &Manifest::default(),
+        )
+        .unwrap();
+        assert_eq!(v1_diff.stats.type_names, ["Inner", "Old", "Test"]);
+        assert!(v1_diff.removed.is_empty());
+
+        let mut buf = Vec::new();
+        let diff = write_changed_definitions(
+            &mut buf,
+            TEST_OPTIONS,
+            &[&v1::Test::INFO],
+            &v1_diff.manifest,
+        )
+        .unwrap();
+        assert!(diff.stats.type_names.is_empty());
+        assert_eq!(diff.manifest, v1_diff.manifest);
+
+        let mut buf = Vec::new();
+        let diff = write_changed_definitions(
+            &mut buf,
+            TEST_OPTIONS,
+            &[&v2::Test::INFO],
+            &v1_diff.manifest,
+        )
+        .unwrap();
+        assert_eq!(diff.removed, ["Old"]);
+        assert_eq_str!(
+            String::from_utf8(buf).unwrap(),
+            r#"export default types;
+export namespace types {
+    export type Inner = {
+        "a": (string)[];
+    };
+    export type Test = {
+        "inner": types.Inner;
+        "old": boolean;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn changed_definitions_shape() {
+        use typescript_type_def::{write_changed_definitions, Manifest};
+
+        mod v1 {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Test {
+                pub a: Vec<String>,
+            }
+        }
+
+        mod v2 {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Test {
+                pub a: [String; 1],
+            }
+        }
+
+        let mut buf = Vec::new();
+        let v1_diff = write_changed_definitions(
+            &mut buf,
+            TEST_OPTIONS,
+            &[&v1::Test::INFO],
+            &Manifest::default(),
+        )
+        .unwrap();
+        let mut buf = Vec::new();
+        let diff = write_changed_definitions(
+            &mut buf,
+            TEST_OPTIONS,
+            &[&v2::Test::INFO],
+            &v1_diff.manifest,
+        )
+        .unwrap();
+        assert_eq!(diff.stats.type_names, ["Test"]);
+    }
+
+    #[test]
+    fn shared_definitions() {
+        #[derive(Serialize, TypeDef)]
+        struct Common {
+            a: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            common: Common,
+            b: usize,
+        }
+
+        let mut shared = SharedDefinitions::new();
+        let mut buf = Vec::new();
+        write_definition_file_with_shared(
+            &mut buf,
+            DefinitionFileOptions {
+                root_namespace: Some("common"),
+                ..TEST_OPTIONS
+            },
+            &[&Common::INFO],
+            &mut shared,
+        )
+        .unwrap();
+        assert_eq_str!(
+            String::from_utf8(buf).unwrap(),
+            r#"export default common;
+export namespace common {
+    export type Common = {
+        "a": string;
+    };
+}
+"#
+        );
+
+        let mut buf = Vec::new();
+        write_definition_file_with_shared(
+            &mut buf,
+            DefinitionFileOptions {
+                header: Some("import common from \"./common\";"),
+                ..TEST_OPTIONS
+            },
+            &[&Test::INFO],
+            &mut shared,
+        )
+        .unwrap();
+        assert_eq_str!(
+            String::from_utf8(buf).unwrap(),
+            r#"import common from "./common";
+export default types;
+export namespace types {
+    export type Usize = number;
+    export type Test = {
+        "common": common.Common;
+        "b": types.Usize;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn deny_unknown_fields() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(deny_unknown_fields)]
+        struct Test {
+            a: String,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = {
+        "a": string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn brand() {
+        #[derive(Serialize, TypeDef)]
+        #[type_def(brand)]
+        struct UserId(String);
+
+        #[derive(Serialize, TypeDef)]
+        #[type_def(brand)]
+        #[serde(rename = "ORDER_ID")]
+        struct OrderId(String);
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            user: UserId,
+            order: OrderId,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&UserId("foo".to_owned())).unwrap(),
+            r#""foo""#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type UserId = (string & {
+        readonly "__brand": "UserId";
+    });
+    export type ORDER_ID = (string & {
+        readonly "__brand": "ORDER_ID";
+    });
+    export type Test = {
+        "user": types.UserId;
+        "order": types.ORDER_ID;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn brand_namespaces() {
+        mod a {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            #[type_def(brand, namespace = "a")]
+            pub struct Id(pub String);
+        }
+
+        mod b {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            #[type_def(brand, namespace = "b")]
+            pub struct Id(pub String);
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: a::Id,
+            b: b::Id,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export namespace a {
+        export type Id = (string & {
+            readonly "__brand": "a.Id";
+        });
+    }
+    export namespace b {
+        export type Id = (string & {
+            readonly "__brand": "b.Id";
+        });
+    }
+    export type Test = {
+        "a": types.a.Id;
+        "b": types.b.Id;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn newtype_map_keys() {
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize, TypeDef, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        struct UserId(String);
+
+        #[derive(Serialize, TypeDef, PartialEq, Eq, PartialOrd, Ord)]
+        #[serde(transparent)]
+        struct OrderId {
+            id: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: HashMap<UserId, usize>,
+            b: BTreeMap<OrderId, String>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: IntoIterator::into_iter([(UserId("x".to_owned()), 1)])
+                    .collect(),
+                b: IntoIterator::into_iter([(
+                    OrderId { id: "y".to_owned() },
+                    "z".to_owned()
+                )])
+                .collect(),
+            })
+            .unwrap(),
+            r#"{"a":{"x":1},"b":{"y":"z"}}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type UserId = string;
+    export type Usize = number;
+    export type OrderId = string;
+    export type Test = {
+        "a": Record<types.UserId, types.Usize>;
+        "b": Record<types.OrderId, string>;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn integer_newtype_map_keys() {
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize, TypeDef, PartialEq, Eq, PartialOrd, Ord)]
+        struct Day(u32);
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: BTreeMap<Day, String>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: IntoIterator::into_iter([(Day(1), "x".to_owned())])
+                    .collect(),
+            })
+            .unwrap(),
+            r#"{"a":{"1":"x"}}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U32 = number;
+    export type Day = types.U32;
+    export type Test = {
+        "a": Record<types.Day, string>;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn by_kind_maps() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(tag = "type")]
+        enum Test {
+            A { a: usize },
+            B,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        enum Test2 {
+            A,
+            B,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Root {
+            a: Test,
+            b: Test2,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Root>(DefinitionFileOptions {
+                by_kind_maps: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Usize = number;
+    export type Test = ({
+        "type": "A";
+        "a": types.Usize;
+    } | {
+        "type": "B";
+    });
+    export type TestByKind = { [K in Test["type"]]: Extract<Test, { "type": K }> };
+    export type Test2 = ("A" | "B");
+    export type Root = {
+        "a": types.Test;
+        "b": types.Test2;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn tag_aliases() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(tag = "kind", rename_all = "snake_case")]
+        enum Test {
+            Circle { radius: f64 },
+            Square { side: f64 },
+            Empty,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        enum Test2 {
+            A,
+            B(String),
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Root {
+            a: Test,
+            b: Test2,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Root>(DefinitionFileOptions {
+                tag_aliases: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type F64 = number;
+    export type Test = ({
+        "kind": "circle";
+        "radius": types.F64;
+    } | {
+        "kind": "square";
+        "side": types.F64;
+    } | {
+        "kind": "empty";
+    });
+    export type TestTag = ("circle" | "square" | "empty");
+    export type Test2 = ("A" | {
+        "B": string;
+    });
+    export type Root = {
+        "a": types.Test;
+        "b": types.Test2;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn handler_maps() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(tag = "kind")]
+        enum Test {
+            A { a: usize },
+            B,
+            C { c: String },
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[serde(tag = "kind")]
+        enum Test2<R> {
+            A { a: R },
+            B,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Root {
+            a: Test,
+            b: Test2<String>,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Root>(DefinitionFileOptions {
+                handler_maps: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Usize = number;
+    export type Test = ({
+        "kind": "A";
+        "a": types.Usize;
+    } | {
+        "kind": "B";
+    } | {
+        "kind": "C";
+        "c": string;
+    });
+    export type TestHandlers<R> = { [K in Test["kind"]]: (value: Extract<Test, { "kind": K }>) => R };
+    export type Test2<R> = ({
+        "kind": "A";
+        "a": R;
+    } | {
+        "kind": "B";
+    });
+    export type Test2Handlers<R_, R> = { [K in Test2<R>["kind"]]: (value: Extract<Test2<R>, { "kind": K }>) => R_ };
+    export type Root = {
+        "a": types.Test;
+        "b": types.Test2<string>;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn generated_type_vars() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(tag = "kind")]
+        enum Test<K> {
+            A { a: K },
+            B,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Root {
+            a: Test<String>,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Root>(DefinitionFileOptions {
+                by_kind_maps: true,
+                handler_maps: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Test<K> = ({
+        "kind": "A";
+        "a": K;
+    } | {
+        "kind": "B";
+    });
+    export type TestByKind<K> = { [K_ in Test<K>["kind"]]: Extract<Test<K>, { "kind": K_ }> };
+    export type TestHandlers<R, K> = { [K_ in Test<K>["kind"]]: (value: Extract<Test<K>, { "kind": K_ }>) => R };
+    export type Root = {
+        "a": types.Test<string>;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn generated_name_conflict() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(tag = "kind")]
+        enum Test {
+            A { a: u8 },
+            B,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct TestByKind {
+            a: Test,
+        }
+
+        let err = write_definition_file::<_, TestByKind>(
+            Vec::new(),
+            DefinitionFileOptions {
+                by_kind_maps: true,
+                ..TEST_OPTIONS
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq_str!(
+            err.to_string(),
+            r#"generated type name "TestByKind" conflicts with a type definition"#
+        );
+        assert_eq!(
+            write_definition_file_fmt::<_, TestByKind>(
+                String::new(),
+                DefinitionFileOptions {
+                    by_kind_maps: true,
+                    ..TEST_OPTIONS
+                },
+            )
+            .unwrap_err(),
+            std::fmt::Error
+        );
+        write_definition_file::<_, TestByKind>(Vec::new(), TEST_OPTIONS)
+            .unwrap();
+    }
+
+    #[test]
+    fn generated_schema_name_conflict() {
+        #[derive(Serialize, TypeDef)]
+        #[type_def(emit_schema)]
+        struct Test {
+            a: u8,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct TestSchema {
+            a: Test,
+        }
+
+        let err =
+            write_definition_file::<_, TestSchema>(Vec::new(), TEST_OPTIONS)
+                .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq_str!(
+            err.to_string(),
+            r#"generated type name "TestSchema" conflicts with a type definition"#
+        );
+    }
+
+    #[test]
+    fn alias() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[serde(alias = "b", alias = "c")]
+            a: String,
+            d: Test2,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        enum Test2 {
+            #[serde(alias = "B")]
+            A,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test2 = "A";
+    export type Test = {
+        "a": string;
+        "d": types.Test2;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn custom_hasher() {
+        use std::{
+            collections::hash_map::DefaultHasher, hash::BuildHasherDefault,
+        };
+
+        type Hasher = BuildHasherDefault<DefaultHasher>;
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: HashMap<String, usize, Hasher>,
+            b: HashSet<String, Hasher>,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Usize = number;
+    export type Test = {
+        "a": Record<string, types.Usize>;
+        "b": (string)[];
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn partial_aliases() {
+        #[derive(Serialize, TypeDef)]
+        struct Inner<T> {
+            x: T,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Inner<String>,
+            b: Vec<String>,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                partial_aliases: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Inner<T> = {
+        "x": T;
+    };
+    export type InnerPartial<T> = Partial<Inner<T>>;
+    export type Test = {
+        "a": types.Inner<string>;
+        "b": (string)[];
+    };
+    export type TestPartial = Partial<Test>;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn partial_aliases_intersections() {
+        #[derive(Serialize, TypeDef)]
+        struct Inner {
+            x: bool,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        enum Kind {
+            A(u8),
+            B(String),
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Flat {
+            #[serde(flatten)]
+            inner: Inner,
+            a: u8,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Tagged {
+            #[serde(flatten)]
+            kind: Kind,
+            a: u8,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Flat,
+            b: Tagged,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                partial_aliases: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Inner = {
+        "x": boolean;
+    };
+    export type InnerPartial = Partial<Inner>;
+    export type U8 = number;
+    export type Flat = (types.Inner & {
+        "a": types.U8;
+    });
+    export type FlatPartial = Partial<Flat>;
+    export type Kind = ({
+        "A": types.U8;
+    } | {
+        "B": string;
+    });
+    export type Tagged = (types.Kind & {
+        "a": types.U8;
+    });
+    export type Test = {
+        "a": types.Flat;
+        "b": types.Tagged;
+    };
+    export type TestPartial = Partial<Test>;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn partial_aliases_conflict() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: u8,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct TestPartial {
+            a: Test,
+        }
+
+        let err = write_definition_file::<_, TestPartial>(
+            Vec::new(),
+            DefinitionFileOptions {
+                partial_aliases: true,
+                ..TEST_OPTIONS
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq_str!(
+            err.to_string(),
+            r#"generated type name "TestPartial" conflicts with a type definition"#
+        );
+    }
+
+    #[test]
+    fn finite_floats() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: f64,
+            b: u8,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                finite_floats: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+
+    /**
+     * JSON cannot represent `NaN` or infinite values, so this is always a
+     * finite number.
+     */
+    export type F64 = (number & {
+        readonly "__brand": "Finite";
+    });
+    export type U8 = number;
+    export type Test = {
+        "a": types.F64;
+        "b": types.U8;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn external_imports() {
+        #[derive(Serialize, TypeDef)]
+        struct Other {
+            x: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Other,
+            b: Vec<Other>,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                external_imports: &[
+                    ("Other", "./other"),
+                    ("Unused", "./unused")
+                ],
+                ..TEST_OPTIONS
+            }),
+            r#"import type { Other } from "./other";
+export default types;
+export namespace types {
+    export type Test = {
+        "a": Other;
+        "b": (Other)[];
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn external_imports_deps_and_aliases() {
+        mod a {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            pub struct Dep {
+                pub y: bool,
+            }
+
+            #[derive(Serialize, TypeDef)]
+            #[type_def(namespace = "a")]
+            pub struct Foo {
+                pub x: Dep,
+            }
+        }
+
+        mod b {
+            use super::*;
+
+            #[derive(Serialize, TypeDef)]
+            #[type_def(namespace = "b")]
+            pub struct Foo {
+                pub x: u8,
+            }
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: a::Foo,
+            b: b::Foo,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                external_imports: &[("a.Foo", "./a"), ("b.Foo", "./b")],
+                ..TEST_OPTIONS
+            }),
+            r#"import type { Foo as a_Foo } from "./a";
+import type { Foo as b_Foo } from "./b";
+export default types;
+export namespace types {
+    export type Test = {
+        "a": a_Foo;
+        "b": b_Foo;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn type_override() {
+        use typescript_type_def::type_expr::TypeUnion;
+
+        fn type_override(def: &'static TypeDefinition) -> Option<TypeExpr> {
+            const NULLABLE_NUMBER: TypeExpr = TypeExpr::Union(TypeUnion {
+                docs: None,
+                members: &[
+                    TypeExpr::ident(Ident("number")),
+                    TypeExpr::ident(Ident("null")),
+                ],
+            });
+
+            match def.name {
+                Ident("F64") => Some(NULLABLE_NUMBER),
+                _ => None,
+            }
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: f64,
+            b: u8,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                type_override: Some(type_override),
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type F64 = (number | null);
+    export type U8 = number;
+    export type Test = {
+        "a": types.F64;
+        "b": types.U8;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn flatten_option() {
+        #[derive(Serialize, TypeDef)]
+        struct Inner {
+            x: bool,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[serde(flatten)]
+            inner: Option<Inner>,
+            a: String,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                inner: Some(Inner { x: true }),
+                a: "a".to_owned(),
+            })
+            .unwrap(),
+            r#"{"x":true,"a":"a"}"#
+        );
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                inner: None,
+                a: "a".to_owned(),
+            })
+            .unwrap(),
+            r#"{"a":"a"}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Inner = {
+        "x": boolean;
+    };
+    export type Test = (Partial<types.Inner> & {
+        "a": string;
+    });
+}
+"#
+        );
+    }
+
+    #[test]
+    fn flatten_tagged_enum() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(tag = "type")]
+        enum Shape {
+            Circle { radius: f64 },
+            Square { side: f64 },
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            id: String,
+            #[serde(flatten)]
+            shape: Shape,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                id: "a".to_owned(),
+                shape: Shape::Circle { radius: 1.0 },
+            })
+            .unwrap(),
+            r#"{"id":"a","type":"Circle","radius":1.0}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type F64 = number;
+    export type Shape = ({
+        "type": "Circle";
+        "radius": types.F64;
+    } | {
+        "type": "Square";
+        "side": types.F64;
+    });
+    export type Test = (types.Shape & {
+        "id": string;
+    });
+}
+"#
+        );
+    }
+
+    #[test]
+    fn flatten_map() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: u8,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            b: Option<bool>,
+            c: String,
+            #[serde(flatten)]
+            extra: HashMap<String, String>,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Same {
+            a: String,
+            #[serde(flatten)]
+            extra: HashMap<String, String>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: 1,
+                b: None,
+                c: "c".to_owned(),
+                extra: vec![("d".to_owned(), "d".to_owned())]
+                    .into_iter()
+                    .collect(),
+            })
+            .unwrap(),
+            r#"{"a":1,"c":"c","d":"d"}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        [key:string]:(string | types.U8 | boolean | undefined);
+        "a": types.U8;
+        "b"?: boolean;
+        "c": string;
+    };
+}
+"#
+        );
+        assert_eq_str!(
+            test_emit::<Same>(),
+            r#"export default types;
+export namespace types {
+    export type Same = {
+        [key:string]:string;
+        "a": string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn flatten_map_value_shapes() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Vec<u8>,
+            b: [u8; 1],
+            #[serde(flatten)]
+            extra: HashMap<String, Vec<u8>>,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        [key:string]:((types.U8)[] | [types.U8]);
+        "a": (types.U8)[];
+        "b": [types.U8];
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn container_default() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(default)]
+        struct Test {
+            a: String,
+            b: Option<usize>,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Usize = number;
+    export type Test = {
+        "a"?: string;
+        "b"?: (types.Usize | null);
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn empty_enum() {
+        #[derive(Serialize, TypeDef)]
+        enum Void {}
+
+        assert_eq_str!(
+            test_emit::<Void>(),
+            r#"export default types;
+export namespace types {
+    export type Void = never;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn foreign_field() {
+        #[derive(Serialize)]
+        #[serde(transparent)]
+        struct ExternalString(String);
+
+        #[derive(Serialize, TypeDef)]
+        #[serde(transparent)]
+        struct ExternalStringWrapper(
+            #[type_def(type_of = "String")] ExternalString,
+        );
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            #[type_def(type_of = "String")]
+            a: ExternalString,
+            b: ExternalStringWrapper,
+            c: String,
+            #[type_def(type_of = "i16")]
+            d: usize,
+            #[type_def(flatten, type_of = "Test3")]
+            e: Test2,
+        }
+
+        #[derive(Serialize)]
+        struct Test2 {
+            foo: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test3 {
+            bar: String,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test3 = {
+        "bar": string;
+    };
+    export type ExternalStringWrapper = string;
+    export type I16 = number;
+    export type Test = (types.Test3 & {
+        "a": string;
+        "b": types.ExternalStringWrapper;
+        "c": string;
+        "d": types.I16;
+    });
+}
+"#
+        );
+    }
+
+    #[test]
+    fn time() {
+        use std::time::{Duration, SystemTime};
+
+        mod millis {
+            use serde::Serializer;
+            use std::time::Duration;
+
+            pub fn serialize<S>(
+                duration: &Duration,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.serialize_u64(duration.as_millis() as u64)
+            }
+        }
+
+        mod iso8601 {
+            use serde::Serializer;
+            use std::time::Duration;
+
+            pub fn serialize<S>(
+                duration: &Duration,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer
+                    .serialize_str(&format!("PT{}S", duration.as_secs_f64()))
+            }
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Duration,
+            b: SystemTime,
+            #[serde(with = "millis")]
+            #[type_def(type_of = "u64")]
+            c: Duration,
+            #[serde(with = "iso8601")]
+            #[type_def(type_of = "String")]
+            d: Duration,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: Duration::from_secs(1),
+                b: SystemTime::UNIX_EPOCH,
+                c: Duration::from_millis(1500),
+                d: Duration::from_millis(1500),
+            })
+            .unwrap(),
+            r#"{"a":{"secs":1,"nanos":0},"b":{"secs_since_epoch":0,"nanos_since_epoch":0},"c":1500,"d":"PT1.5S"}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U64 = number;
+    export type U32 = number;
+    export type Test = {
+        "a": {
+            "secs": types.U64;
+            "nanos": types.U32;
+        };
+        "b": {
+            "secs_since_epoch": types.U64;
+            "nanos_since_epoch": types.U32;
+        };
+        "c": types.U64;
+        "d": string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        use std::ops::{Bound, Range, RangeFrom, RangeInclusive, RangeTo};
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Bound<u8>,
+            b: Range<u8>,
+            c: RangeInclusive<u8>,
+            d: RangeFrom<u8>,
+            e: RangeTo<u8>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&[
+                Bound::Included(1),
+                Bound::Excluded(2),
+                Bound::Unbounded
+            ])
+            .unwrap(),
+            r#"[{"Included":1},{"Excluded":2},"Unbounded"]"#
+        );
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: Bound::Unbounded,
+                b: 1..2,
+                c: 1..=2,
+                d: 1..,
+                e: ..2,
+            })
+            .unwrap(),
+            r#"{"a":"Unbounded","b":{"start":1,"end":2},"c":{"start":1,"end":2},"d":{"start":1},"e":{"end":2}}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a": ({
+            "Included": types.U8;
+        } | {
+            "Excluded": types.U8;
+        } | "Unbounded");
+        "b": {
+            "start": types.U8;
+            "end": types.U8;
+        };
+        "c": {
+            "start": types.U8;
+            "end": types.U8;
+        };
+        "d": {
+            "start": types.U8;
+        };
+        "e": {
+            "end": types.U8;
+        };
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn no_root_namespace() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: usize,
+        }
+
+        let mut buf = Vec::new();
+        let options = DefinitionFileOptions {
+            header: None,
+            root_namespace: None,
+            strict_empty_objects: false,
+            all_type_names: None,
+            line_ending: LineEnding::Lf,
+            module_system: ModuleSystem::Esm,
+            readonly_arrays: false,
+            inline_single_use: false,
+            by_kind_maps: false,
+            partial_aliases: false,
+            finite_floats: false,
+            external_imports: &[],
+            any_type: None,
+            type_override: None,
+            prettier_ignore: false,
+            header_style: HeaderStyle::Verbatim,
+            max_union_width: None,
+            map_style: MapStyle::Record,
+            utf8_bom: false,
+            tag_aliases: false,
+            package_docs: None,
+            objects_as_interfaces: false,
+            declare_module: None,
+            handler_maps: false,
+            root_namespace_docs: None,
+            generated_from: None,
+        };
+        write_definition_file::<_, Test>(&mut buf, options).unwrap();
+        let result = String::from_utf8(buf).unwrap();
+
+        assert_eq_str!(
+            result,
+            r#"export type Usize = number;
+export type Test = {
+    "a": Usize;
+};
+"#
+        );
+    }
+
+    #[test]
+    fn module_system() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: usize,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                module_system: ModuleSystem::Esm,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export type Usize = number;
+    export type Test = {
+        "a": types.Usize;
+    };
+}
+"#
+        );
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                module_system: ModuleSystem::CommonJs,
+                ..TEST_OPTIONS
+            }),
+            r#"export = types;
+namespace types {
+    export type Usize = number;
+    export type Test = {
+        "a": types.Usize;
+    };
+}
+"#
+        );
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                module_system: ModuleSystem::CommonJs,
+                all_type_names: Some("allTypeNames"),
+                ..TEST_OPTIONS
+            }),
+            r#"export = types;
+namespace types {
+    export type Usize = number;
+    export type Test = {
+        "a": types.Usize;
+    };
+    export const allTypeNames = ["Usize", "Test"] as const;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn prettier_ignore() {
+        #[derive(Serialize, TypeDef)]
+        #[type_def(namespace = "x")]
+        struct Inner {
+            a: usize,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Inner,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                prettier_ignore: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+// prettier-ignore
+export namespace types {
+    export type Usize = number;
+    export namespace x {
+        export type Inner = {
+            "a": types.Usize;
+        };
+    }
+    export type Test = {
+        "a": types.x.Inner;
+    };
+}
+"#
+        );
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                prettier_ignore: true,
+                root_namespace: None,
+                ..TEST_OPTIONS
+            }),
+            r#"// prettier-ignore
+export type Usize = number;
+// prettier-ignore
+export namespace x {
+    export type Inner = {
+        "a": Usize;
+    };
+}
+// prettier-ignore
+export type Test = {
+    "a": x.Inner;
+};
+"#
+        );
+    }
+
+    #[test]
+    fn header_styles() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: String,
+        }
+
+        let options = DefinitionFileOptions {
+            header: Some("Copyright (c) Foo\n\nAUTO-GENERATED"),
+            ..TEST_OPTIONS
+        };
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                header_style: HeaderStyle::Line,
+                ..options
+            }),
+            r#"// Copyright (c) Foo
+//
+// AUTO-GENERATED
+export default types;
+export namespace types {
+    export type Test = {
+        "a": string;
+    };
+}
+"#
+        );
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                header_style: HeaderStyle::Block,
+                ..options
+            }),
+            r#"/*
+ * Copyright (c) Foo
+ *
+ * AUTO-GENERATED
+ */
+export default types;
+export namespace types {
+    export type Test = {
+        "a": string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn no_header() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: String,
+        }
+
+        let result = test_emit_with_options::<Test>(DefinitionFileOptions {
+            header: None,
+            ..TEST_OPTIONS
+        });
+        assert!(result.starts_with("export default types;\n"));
+        let result = test_emit_with_options::<Test>(DefinitionFileOptions {
+            header: None,
+            root_namespace: None,
+            ..TEST_OPTIONS
+        });
+        assert!(result.starts_with("export type Test = {\n"));
+        let result = test_emit_with_options::<Test>(DefinitionFileOptions {
+            header: None,
+            header_style: HeaderStyle::Block,
+            ..TEST_OPTIONS
+        });
+        assert!(result.starts_with("export default types;\n"));
+    }
+
+    #[test]
+    fn utf8_bom() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: String,
+        }
+
+        let options = DefinitionFileOptions {
+            header: Some("// header"),
+            ..TEST_OPTIONS
+        };
+        let mut buf = Vec::new();
+        write_definition_file::<_, Test>(&mut buf, options).unwrap();
+        assert!(buf.starts_with(b"// header\n"));
+        let mut buf = Vec::new();
+        write_definition_file::<_, Test>(
+            &mut buf,
+            DefinitionFileOptions {
+                utf8_bom: true,
+                ..options
+            },
+        )
+        .unwrap();
+        assert!(buf.starts_with(b"\xEF\xBB\xBF// header\n"));
+    }
+
+    #[test]
+    fn interfaces() {
+        #[derive(Serialize, TypeDef)]
+        #[type_def(interface)]
+        struct A {
+            a: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[type_def(type_alias)]
+        struct B {
+            b: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        struct C {
+            a: A,
+            b: B,
+            c: D,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        enum D {
+            X,
+            Y,
+        }
+
+        assert_eq_str!(
+            test_emit::<C>(),
+            r#"export default types;
+export namespace types {
+    export interface A {
+        "a": string;
+    }
+    export type B = {
+        "b": string;
+    };
+    export type D = ("X" | "Y");
+    export type C = {
+        "a": types.A;
+        "b": types.B;
+        "c": types.D;
+    };
+}
+"#
+        );
+        assert_eq_str!(
+            test_emit_with_options::<C>(DefinitionFileOptions {
+                objects_as_interfaces: true,
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+export namespace types {
+    export interface A {
+        "a": string;
+    }
+    export type B = {
+        "b": string;
+    };
+    export type D = ("X" | "Y");
+    export interface C {
+        "a": types.A;
+        "b": types.B;
+        "c": types.D;
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn declare_module() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: u8,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                header: Some("// header"),
+                declare_module: Some("my-types"),
+                ..TEST_OPTIONS
+            }),
+            r#"// header
+declare module "my-types" {
+    export default types;
+    export namespace types {
+        export type U8 = number;
+        export type Test = {
+            "a": types.U8;
+        };
+    }
+}
+"#
+        );
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                root_namespace: None,
+                declare_module: Some("my-types"),
+                ..TEST_OPTIONS
+            }),
+            r#"declare module "my-types" {
+    export type U8 = number;
+    export type Test = {
+        "a": U8;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn root_namespace_docs() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: String,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                root_namespace_docs: Some("Types of the API.\n\nSee the docs."),
+                ..TEST_OPTIONS
+            }),
+            r#"export default types;
+/**
+ * Types of the API.
+ *
+ * See the docs.
+ */
+export namespace types {
+    export type Test = {
+        "a": string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn generated_from() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: String,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                header: Some("// header"),
+                generated_from: Some(("my-crate", "1.2.3")),
+                ..TEST_OPTIONS
+            }),
+            r#"// header
+// Generated from my-crate v1.2.3
+export default types;
+export namespace types {
+    export type Test = {
+        "a": string;
+    };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn package_docs() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: String,
+        }
+
+        assert_eq_str!(
+            test_emit_with_options::<Test>(DefinitionFileOptions {
+                header: Some("// header"),
+                package_docs: Some("Types of the API.\n\nSee the server docs."),
+                ..TEST_OPTIONS
+            }),
+            r#"// header
+/**
+ * Types of the API.
+ *
+ * See the server docs.
+ *
+ * @packageDocumentation
+ */
+export default types;
 export namespace types {
     export type Test = {
-        "a"?: string;
-        "b"?: string;
+        "a": string;
     };
 }
 "#
@@ -732,117 +4769,426 @@ export namespace types {
     }
 
     #[test]
-    fn foreign_field() {
-        #[derive(Serialize)]
-        #[serde(transparent)]
-        struct ExternalString(String);
+    fn crlf_line_endings() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: usize,
+        }
+
+        let result = test_emit_with_options::<Test>(DefinitionFileOptions {
+            header: Some("// header line 1\n// header line 2\n"),
+            line_ending: LineEnding::Crlf,
+            ..TEST_OPTIONS
+        });
+
+        assert_eq!(
+            result,
+            "// header line 1\r\n// header line 2\r\n\r\n\
+             export default types;\r\n\
+             export namespace types {\r\n    \
+             export type Usize = number;\r\n    \
+             export type Test = {\r\n        \
+             \"a\": types.Usize;\r\n    \
+             };\r\n\
+             }\r\n"
+        );
+    }
+}
+
+#[cfg(feature = "json_value")]
+mod json_value {
+    use super::test_emit;
+    use serde::Serialize;
+    use std::collections::HashMap;
+    use typescript_type_def::TypeDef;
 
+    #[test]
+    fn json_value() {
         #[derive(Serialize, TypeDef)]
-        #[serde(transparent)]
-        struct ExternalStringWrapper(
-            #[type_def(type_of = "String")] ExternalString,
+        struct Test {
+            a: String,
+            b: serde_json::Value,
+            c: serde_json::Number,
+        }
+
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type JSONValue = (null | boolean | number | string | (JSONValue)[] | {
+        [key:string]:JSONValue;
+    });
+    export type Test = {
+        "a": string;
+        "b": types.JSONValue;
+        "c": number;
+    };
+}
+"#
         );
+    }
 
+    #[test]
+    fn json_value_collections() {
         #[derive(Serialize, TypeDef)]
         struct Test {
-            #[type_def(type_of = "String")]
-            a: ExternalString,
-            b: ExternalStringWrapper,
-            c: String,
-            #[type_def(type_of = "i16")]
-            d: usize,
-            #[type_def(flatten, type_of = "Test3")]
-            e: Test2,
+            a: Vec<serde_json::Value>,
+            b: HashMap<String, serde_json::Value>,
+            c: serde_json::Map<String, serde_json::Value>,
+            d: Option<serde_json::Value>,
         }
 
-        #[derive(Serialize)]
-        struct Test2 {
-            foo: String,
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type JSONValue = (null | boolean | number | string | (JSONValue)[] | {
+        [key:string]:JSONValue;
+    });
+    export type Test = {
+        "a": (types.JSONValue)[];
+        "b": Record<string, types.JSONValue>;
+        "c": Record<string, types.JSONValue>;
+        "d": (types.JSONValue | null);
+    };
+}
+"#
+        );
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+mod arrayvec {
+    use super::test_emit;
+    use serde::Serialize;
+    use typescript_type_def::TypeDef;
+
+    #[test]
+    fn arrayvec() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: ::arrayvec::ArrayVec<u8, 4>,
+            b: ::arrayvec::ArrayString<16>,
         }
 
+        let mut a = ::arrayvec::ArrayVec::new();
+        a.push(1);
+        a.push(2);
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a,
+                b: ::arrayvec::ArrayString::from("foo").unwrap(),
+            })
+            .unwrap(),
+            r#"{"a":[1,2],"b":"foo"}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type U8 = number;
+    export type Test = {
+        "a": (types.U8)[];
+        "b": string;
+    };
+}
+"#
+        );
+    }
+}
+
+#[cfg(feature = "smallvec")]
+mod smallvec {
+    use super::test_emit;
+    use serde::Serialize;
+    use typescript_type_def::TypeDef;
+
+    #[test]
+    fn smallvec() {
         #[derive(Serialize, TypeDef)]
-        struct Test3 {
-            bar: String,
+        struct Test {
+            a: ::smallvec::SmallVec<[u32; 4]>,
         }
 
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: ::smallvec::smallvec![1, 2, 3],
+            })
+            .unwrap(),
+            r#"{"a":[1,2,3]}"#
+        );
         assert_eq_str!(
             test_emit::<Test>(),
             r#"export default types;
 export namespace types {
-    export type Test3 = {
-        "bar": string;
+    export type U32 = number;
+    export type Test = {
+        "a": (types.U32)[];
     };
-    export type ExternalStringWrapper = string;
-    export type I16 = number;
-    export type Test = (types.Test3 & {
+}
+"#
+        );
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono {
+    use super::test_emit;
+    use ::chrono::{DateTime, Month, NaiveDate, TimeZone, Utc, Weekday};
+    use serde::Serialize;
+    use typescript_type_def::TypeDef;
+
+    #[test]
+    fn chrono() {
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: DateTime<Utc>,
+            b: NaiveDate,
+            c: Weekday,
+            d: Month,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test {
+                a: Utc.timestamp_opt(0, 0).unwrap(),
+                b: NaiveDate::from_ymd_opt(2020, 1, 2).unwrap(),
+                c: Weekday::Mon,
+                d: Month::January,
+            })
+            .unwrap(),
+            r#"{"a":"1970-01-01T00:00:00Z","b":"2020-01-02","c":"Mon","d":"January"}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = {
         "a": string;
-        "b": types.ExternalStringWrapper;
+        "b": string;
         "c": string;
-        "d": types.I16;
-    });
+        "d": string;
+    };
 }
 "#
         );
     }
 
     #[test]
-    fn no_root_namespace() {
+    fn chrono_timestamps() {
+        use ::chrono::serde::{ts_milliseconds, ts_seconds_option};
+
         #[derive(Serialize, TypeDef)]
         struct Test {
-            a: usize,
+            #[serde(with = "::chrono::serde::ts_seconds")]
+            a: DateTime<Utc>,
+            #[serde(with = "ts_milliseconds")]
+            b: DateTime<Utc>,
+            #[serde(with = "ts_seconds_option")]
+            c: Option<DateTime<Utc>>,
+            #[serde(
+                with = "ts_seconds_option",
+                skip_serializing_if = "Option::is_none"
+            )]
+            d: Option<DateTime<Utc>>,
         }
 
-        let mut buf = Vec::new();
-        let options = DefinitionFileOptions {
-            header: None,
-            root_namespace: None,
-        };
-        write_definition_file::<_, Test>(&mut buf, options).unwrap();
-        let result = String::from_utf8(buf).unwrap();
-
         assert_eq_str!(
-            result,
-            r#"export type Usize = number;
-export type Test = {
-    "a": Usize;
-};
+            serde_json::to_string(&Test {
+                a: Utc.timestamp_opt(1, 0).unwrap(),
+                b: Utc.timestamp_opt(1, 0).unwrap(),
+                c: None,
+                d: None,
+            })
+            .unwrap(),
+            r#"{"a":1,"b":1000,"c":null}"#
+        );
+        assert_eq_str!(
+            test_emit::<Test>(),
+            r#"export default types;
+export namespace types {
+    export type Test = {
+        "a": number;
+        "b": number;
+        "c": (number | null);
+        "d"?: number;
+    };
+}
 "#
         );
     }
 }
 
-#[cfg(feature = "json_value")]
-mod json_value {
+#[cfg(feature = "jiff")]
+mod jiff {
     use super::test_emit;
-    use serde::Serialize;
+    use ::jiff::{
+        civil::{Date, DateTime, Time},
+        Span, Timestamp,
+    };
     use typescript_type_def::TypeDef;
+
     #[test]
-    fn json_value() {
-        #[derive(Serialize, TypeDef)]
+    fn jiff() {
+        // `jiff` is not a dev-dependency with its `serde` feature, so only the
+        // definition is checked
+        #[derive(TypeDef)]
         struct Test {
-            a: String,
-            b: serde_json::Value,
-            c: serde_json::Number,
+            a: Timestamp,
+            b: Date,
+            c: Time,
+            d: DateTime,
+            e: Option<Span>,
         }
 
         assert_eq_str!(
             test_emit::<Test>(),
             r#"export default types;
 export namespace types {
-    export type JSONValue = (null | boolean | number | string | (JSONValue)[] | {
-        [key:string]:JSONValue;
-    });
     export type Test = {
         "a": string;
-        "b": types.JSONValue;
-        "c": number;
+        "b": string;
+        "c": string;
+        "d": string;
+        "e": (string | null);
+    };
+}
+"#
+        );
+    }
+}
+
+#[cfg(feature = "fetch_client")]
+mod fetch_client {
+    use serde::Serialize;
+    use typescript_type_def::{
+        fetch_client::{write_fetch_client, Route},
+        TypeDef,
     };
+
+    #[test]
+    fn fetch_client() {
+        #[derive(Serialize, TypeDef)]
+        struct User {
+            name: String,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[type_def(route = "GET /users/:id", response = "User")]
+        struct GetUser {
+            id: usize,
+        }
+
+        #[derive(Serialize, TypeDef)]
+        #[type_def(route = "PUT /users/:id/name")]
+        struct RenameUser {
+            id: usize,
+            name: String,
+        }
+
+        let mut buf = Vec::new();
+        write_fetch_client(
+            &mut buf,
+            Some("types"),
+            &[GetUser::ROUTE, RenameUser::ROUTE],
+        )
+        .unwrap();
+        assert_eq_str!(
+            String::from_utf8(buf).unwrap(),
+            r#"export async function getUser(request: types.GetUser): Promise<types.User> {
+    const response = await fetch(`/users/${encodeURIComponent(String(request["id"]))}`, {
+        method: "GET",
+    });
+    if (!response.ok) {
+        throw new Error(response.statusText);
+    }
+    return await response.json();
+}
+export async function renameUser(request: types.RenameUser): Promise<void> {
+    const response = await fetch(`/users/${encodeURIComponent(String(request["id"]))}/name`, {
+        method: "PUT",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify(request),
+    });
+    if (!response.ok) {
+        throw new Error(response.statusText);
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn fetch_client_renamed_params() {
+        #[derive(Serialize, TypeDef)]
+        #[serde(rename_all = "camelCase")]
+        #[type_def(route = "DELETE /users/:user_id/posts/:post")]
+        struct DeletePost {
+            user_id: usize,
+            #[serde(rename = "post")]
+            post_id: usize,
+        }
+
+        let mut buf = Vec::new();
+        write_fetch_client(&mut buf, Some("types"), &[DeletePost::ROUTE])
+            .unwrap();
+        assert_eq_str!(
+            String::from_utf8(buf).unwrap(),
+            r#"export async function deletePost(request: types.DeletePost): Promise<void> {
+    const response = await fetch(`/users/${encodeURIComponent(String(request["userId"]))}/posts/${encodeURIComponent(String(request["post"]))}`, {
+        method: "DELETE",
+    });
+    if (!response.ok) {
+        throw new Error(response.statusText);
+    }
 }
 "#
         );
     }
 }
 
+#[cfg(feature = "serde")]
+mod serde_ir {
+    use serde::Serialize;
+    use typescript_type_def::TypeDef;
+
+    #[test]
+    fn serialize_type_info() {
+        /// A test.
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: Option<String>,
+        }
+
+        assert_eq_str!(
+            serde_json::to_string(&Test::INFO).unwrap(),
+            r#"{"Defined":{"def":{"docs":"A test.","path":[],"name":"Test","generic_vars":[],"generic_defaults":[],"def":{"Object":{"docs":null,"index_signature":null,"fields":[{"docs":null,"name":{"docs":null,"value":"a"},"optional":false,"readonly":false,"type":{"Ref":{"Native":{"ref":{"Union":{"docs":null,"members":[{"Ref":{"Native":{"ref":{"Name":{"path":[],"name":"string","generic_args":[]}}}}},{"Name":{"path":[],"name":"null","generic_args":[]}}]}}}}}}]}},"emit_schema":false,"interface":null},"generic_args":[]}}"#
+        );
+    }
+
+    #[test]
+    fn manifest() {
+        use typescript_type_def::{write_changed_definitions, Manifest};
+
+        #[derive(Serialize, TypeDef)]
+        struct Test {
+            a: String,
+        }
+
+        let mut buf = Vec::new();
+        let diff = write_changed_definitions(
+            &mut buf,
+            Default::default(),
+            &[&Test::INFO],
+            &Manifest::default(),
+        )
+        .unwrap();
+        let json = serde_json::to_string(&diff.manifest).unwrap();
+        assert!(json.starts_with(r#"{"type_hashes":{"Test":"#));
+        let manifest: Manifest = serde_json::from_str(&json).unwrap();
+        assert_eq!(manifest, diff.manifest);
+    }
+}
+
 mod write_ref_expr {
     use super::*;
 